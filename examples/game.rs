@@ -104,7 +104,7 @@ impl Astacia {
 }
 
 impl Engine for Astacia {
-    fn init(&mut self, api: &mut dyn DoryenApi) {
+    fn init(&mut self, _api: &mut dyn DoryenApi) {
         self.ctx
             .push_color(ui::ColorCode::Background, (0, 0, 0, 255));
         self.ctx
@@ -117,8 +117,8 @@ impl Engine for Astacia {
             .push_color(ui::ColorCode::ButtonBackgroundFocus, (100, 100, 100, 255));
         self.ctx
             .push_color(ui::ColorCode::Text, (200, 200, 80, 255));
-        api.con().register_color("grey", (180, 180, 180, 255));
-        api.con().register_color("text", (200, 200, 80, 255));
+        self.ctx.register_color("grey", (180, 180, 180, 255));
+        self.ctx.register_color("text", (200, 200, 80, 255));
     }
     fn update(&mut self, api: &mut dyn DoryenApi) -> Option<UpdateEvent> {
         ui::update_doryen_input_data(api, &mut self.ctx);
@@ -66,9 +66,7 @@ impl RfxGen {
         self.ctx.vbox_end();
     }
     fn middle_column(&mut self) {
-        self.ctx
-            .flexgrid_begin("sliders", &[15, 15, 5], 0)
-            .padding(1);
+        self.ctx.flexgrid_begin("sliders", &[15, 15], 0).padding(1);
 
         //self.ctx.vbox_begin("sliders").padding(1).min_width(36);
         self.slider("volume", 0.0, 100.0, 60.0, true);
@@ -150,19 +148,23 @@ impl RfxGen {
     fn slider(&mut self, label: &str, min_val: f32, max_val: f32, start_val: f32, use_int: bool) {
         self.ctx.label(label).align(ui::TextAlign::Right);
         if use_int {
-            let value =
-                self.ctx
-                    .islider(label, 10, min_val as i32, max_val as i32, start_val as i32);
-            self.ctx.label(&format!("{}", value));
+            self.ctx.show_value().islider(
+                label,
+                15,
+                min_val as i32,
+                max_val as i32,
+                start_val as i32,
+            );
         } else {
-            let value = self.ctx.fslider(label, 10, min_val, max_val, start_val);
-            self.ctx.label(&format!("{:.2}", value));
+            self.ctx
+                .show_value()
+                .fslider(label, 15, min_val, max_val, start_val);
         }
     }
 }
 
 impl Engine for RfxGen {
-    fn init(&mut self, api: &mut dyn DoryenApi) {
+    fn init(&mut self, _api: &mut dyn DoryenApi) {
         self.ctx
             .push_color(ui::ColorCode::Background, (245, 245, 245, 255));
         self.ctx
@@ -175,8 +177,8 @@ impl Engine for RfxGen {
             .push_color(ui::ColorCode::ButtonBackgroundFocus, (151, 232, 235, 255));
         self.ctx
             .push_color(ui::ColorCode::Text, (104, 104, 104, 255));
-        api.con().register_color("grey", (180, 180, 180, 255));
-        api.con().register_color("text", (200, 200, 80, 255));
+        self.ctx.register_color("grey", (180, 180, 180, 255));
+        self.ctx.register_color("text", (200, 200, 80, 255));
     }
     fn update(&mut self, api: &mut dyn DoryenApi) -> Option<UpdateEvent> {
         ui::update_doryen_input_data(api, &mut self.ctx);
@@ -131,32 +131,26 @@ impl ScreamTracker {
     }
     fn bottom(&mut self) {
         self.ctx.move_cursor(2, 4);
+        let mut columns = vec![ui::Column::new("", 2)];
+        for i in 1..=8 {
+            columns.push(ui::Column::new(&format!("{:02} : A{}", i, i), 8));
+        }
         self.ctx
-            .flexgrid_begin("table", &[2, 8, 8, 8, 8, 8, 8, 8, 8], 21)
-            .hpadding(1);
-        {
-            // table header
-            self.ctx.label("");
-            self.ctx
-                .push_color(ui::ColorCode::Background, (129, 61, 0, 255));
-            for i in 1..=8 {
-                self.ctx.label(&format!("{:02} : A{}", i, i));
-            }
-            self.ctx.pop_color(ui::ColorCode::Background);
-            // table content
-            for i in 43..64 {
-                self.ctx.push_color(ui::ColorCode::Text, (129, 61, 0, 255));
-                self.ctx.label(&format!("{}", i));
-                self.ctx.pop_color(ui::ColorCode::Text);
-                self.black_and_white(ui::ColorCode::ButtonBackground, ui::ColorCode::Text);
-                for j in 1..=8 {
-                    self.ctx.button(&format!("b{}-{}", i, j), "...  .00");
-                }
-                self.pop_color(ui::ColorCode::ButtonBackground, ui::ColorCode::Text);
+            .push_color(ui::ColorCode::ButtonBackground, (129, 61, 0, 255));
+        let rows = self.ctx.table_begin("pattern", &columns, 22, 64);
+        self.ctx.pop_color(ui::ColorCode::ButtonBackground);
+        for i in rows {
+            self.ctx.table_row(i);
+            self.ctx.push_color(ui::ColorCode::Text, (129, 61, 0, 255));
+            self.ctx.label(&format!("{:02}", i));
+            self.ctx.pop_color(ui::ColorCode::Text);
+            self.black_and_white(ui::ColorCode::ButtonBackground, ui::ColorCode::Text);
+            for j in 1..=8 {
+                self.ctx.button(&format!("b{}-{}", i, j), "...  .00");
             }
+            self.pop_color(ui::ColorCode::ButtonBackground, ui::ColorCode::Text);
         }
-        self.ctx.flexgrid_end();
-        self.ctx.hbox_end();
+        self.ctx.table_end();
     }
     fn black_and_white(&mut self, back: ui::ColorCode, fore: ui::ColorCode) {
         self.ctx.push_color(fore, (255, 255, 255, 255));
@@ -169,7 +163,7 @@ impl ScreamTracker {
 }
 
 impl Engine for ScreamTracker {
-    fn init(&mut self, api: &mut dyn DoryenApi) {
+    fn init(&mut self, _api: &mut dyn DoryenApi) {
         self.ctx
             .push_color(ui::ColorCode::Background, (228, 141, 88, 255));
         self.ctx
@@ -182,7 +176,7 @@ impl Engine for ScreamTracker {
             .push_color(ui::ColorCode::ButtonBackgroundFocus, (151, 232, 235, 255));
         self.ctx
             .push_color(ui::ColorCode::Text, (255, 197, 134, 255));
-        api.con().register_color("label", (129, 61, 0, 255));
+        self.ctx.register_color("label", (129, 61, 0, 255));
     }
     fn update(&mut self, api: &mut dyn DoryenApi) -> Option<UpdateEvent> {
         ui::update_doryen_input_data(api, &mut self.ctx);
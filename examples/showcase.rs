@@ -123,7 +123,7 @@ impl Showcase {
 }
 
 impl Engine for Showcase {
-    fn init(&mut self, api: &mut dyn DoryenApi) {
+    fn init(&mut self, _api: &mut dyn DoryenApi) {
         self.ctx
             .push_color(ui::ColorCode::Background, (245, 245, 245, 255));
         self.ctx
@@ -136,8 +136,8 @@ impl Engine for Showcase {
             .push_color(ui::ColorCode::ButtonBackgroundFocus, (151, 232, 235, 255));
         self.ctx
             .push_color(ui::ColorCode::Text, (104, 104, 104, 255));
-        api.con().register_color("yellow", (200, 200, 100, 255));
-        api.con().register_color("orange", (150, 150, 50, 255));
+        self.ctx.register_color("yellow", (200, 200, 100, 255));
+        self.ctx.register_color("orange", (150, 150, 50, 255));
     }
     fn update(&mut self, api: &mut dyn DoryenApi) -> Option<UpdateEvent> {
         ui::update_doryen_input_data(api, &mut self.ctx);
@@ -0,0 +1,94 @@
+extern crate crossterm;
+extern crate doryen_ui;
+
+use std::io::{stdout, Write};
+use std::time::{Duration, Instant};
+
+use crossterm::cursor::{Hide, Show};
+use crossterm::event::{
+    self, DisableMouseCapture, EnableMouseCapture, Event, KeyCode, KeyModifiers,
+};
+use crossterm::execute;
+use crossterm::terminal::{
+    disable_raw_mode, enable_raw_mode, size, EnterAlternateScreen, LeaveAlternateScreen,
+};
+use doryen_ui as ui;
+
+/// returns true when the player confirmed they want to quit
+fn build_ui(ctx: &mut ui::Context, counter: &mut i32, quit_asked: &mut bool) -> bool {
+    ctx.begin();
+    ctx.frame_window_begin("main", "doryen-ui in a terminal", 2, 1, 34, 10)
+        .margin(2)
+        .padding(1);
+    ctx.label(&format!("clicked {} times", counter));
+    if ctx
+        .button("click", "click me")
+        .tooltip("#[red]Ctrl-Q#[] to quit")
+        .pressed()
+    {
+        *counter += 1;
+    }
+    ctx.checkbox("check", "a checkbox", false);
+    ctx.textbox("name", 20, None, Some("type here"));
+    ctx.frame_window_end();
+    let mut quit = false;
+    if *quit_asked {
+        match ctx.confirm("quit", "Quit", "Really quit?") {
+            Some(ui::DialogResult::Yes) => quit = true,
+            Some(_) => *quit_asked = false,
+            None => (),
+        }
+    }
+    ctx.end();
+    quit
+}
+
+fn main() -> crossterm::Result<()> {
+    let mut out = stdout();
+    enable_raw_mode()?;
+    execute!(out, EnterAlternateScreen, EnableMouseCapture, Hide)?;
+    let (width, height) = size()?;
+    let mut renderer = ui::TerminalRenderer::new(width as i32, height as i32);
+    let mut ctx = ui::Context::new();
+    ctx.input_screen_size(width as i32, height as i32);
+    ctx.set_modal_dimming(Some((0, 0, 0, 128)));
+    ctx.register_color("red", (255, 80, 80, 255));
+    let mut counter = 0;
+    let mut quit_asked = false;
+    let mut last_frame = Instant::now();
+    'main: loop {
+        let mut events = Vec::new();
+        while event::poll(Duration::from_millis(if events.is_empty() {
+            30
+        } else {
+            0
+        }))? {
+            let event = event::read()?;
+            match event {
+                Event::Key(key)
+                    if key.code == KeyCode::Char('q')
+                        && key.modifiers.contains(KeyModifiers::CONTROL) =>
+                {
+                    quit_asked = true
+                }
+                Event::Resize(w, h) => renderer.resize(w as i32, h as i32),
+                _ => (),
+            }
+            events.push(event);
+        }
+        ui::update_terminal_input_data(&events, &mut ctx);
+        // the frame rate depends on the events
+        ctx.input_time(last_frame.elapsed().as_secs_f32());
+        last_frame = Instant::now();
+        if build_ui(&mut ctx, &mut counter, &mut quit_asked) {
+            break 'main;
+        }
+        renderer.clear((0, 0, 0, 255));
+        ctx.render(&mut renderer);
+        renderer.flush(&mut out)?;
+    }
+    execute!(out, Show, DisableMouseCapture, LeaveAlternateScreen)?;
+    disable_raw_mode()?;
+    out.flush()?;
+    Ok(())
+}
@@ -0,0 +1,118 @@
+use crate::{color_blend, derived_id, Color, ColorCode, Context, Id, Tween};
+
+/// duration of the hover color fade in seconds
+const HOVER_FADE_TIME: f32 = 0.15;
+
+/// speed curve of a tweened value
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Easing {
+    Linear,
+    /// starts slowly
+    EaseIn,
+    /// ends slowly
+    EaseOut,
+    /// starts and ends slowly
+    EaseInOut,
+}
+
+impl Easing {
+    /// progression of the value for a time coefficient between 0.0 and 1.0
+    pub fn apply(self, t: f32) -> f32 {
+        match self {
+            Easing::Linear => t,
+            Easing::EaseIn => t * t,
+            Easing::EaseOut => 1.0 - (1.0 - t) * (1.0 - t),
+            Easing::EaseInOut if t < 0.5 => 2.0 * t * t,
+            Easing::EaseInOut => 1.0 - (2.0 - 2.0 * t) * (2.0 - 2.0 * t) / 2.0,
+        }
+    }
+}
+
+impl Tween {
+    fn value(&self, time: f64) -> f32 {
+        if self.duration <= 0.0 {
+            return self.to;
+        }
+        let t = ((time - self.start) as f32 / self.duration).clamp(0.0, 1.0);
+        self.from + (self.to - self.from) * self.easing.apply(t)
+    }
+}
+
+impl Context {
+    // =======================================================
+    //
+    // Animations
+    //
+    // =======================================================
+    /// time in seconds since the context creation (see input_time)
+    pub fn time(&self) -> f64 {
+        self.time
+    }
+    /// a value moving smoothly toward target. When target changes, the value goes
+    /// from its current state to the new target in duration seconds.
+    /// The first call for an id returns target.
+    pub fn tween(&mut self, id: Id, target: f32, duration: f32, easing: Easing) -> f32 {
+        let time = self.time;
+        let tween = self.tweens.entry(id).or_insert(Tween {
+            from: target,
+            to: target,
+            start: time,
+            duration,
+            easing,
+            used: true,
+        });
+        if tween.to != target {
+            *tween = Tween {
+                from: tween.value(time),
+                to: target,
+                start: time,
+                duration,
+                easing,
+                used: true,
+            };
+        }
+        tween.used = true;
+        tween.value(time)
+    }
+    /// stops the animation of a tweened value on value
+    pub(crate) fn snap_tween(&mut self, id: Id, value: f32) {
+        if let Some(tween) = self.tweens.get_mut(&id) {
+            tween.from = value;
+            tween.to = value;
+        }
+    }
+    /// forgets the finished animations that were not updated during this frame
+    pub(crate) fn prune_tweens(&mut self) {
+        let time = self.time;
+        self.tweens.retain(|_, tween| {
+            let keep = tween.used || time < tween.start + f64::from(tween.duration);
+            tween.used = false;
+            keep
+        });
+    }
+    /// the colors of a widget, fading to the hover colors when the mouse enters it
+    pub(crate) fn hover_colors(
+        &mut self,
+        id: Id,
+        hover: bool,
+        (background_code, foreground_code): (ColorCode, ColorCode),
+    ) -> (Color, Color) {
+        let coef = self.tween(
+            derived_id(id, "hover"),
+            if hover { 1.0 } else { 0.0 },
+            HOVER_FADE_TIME,
+            Easing::EaseOut,
+        );
+        let back = color_blend(
+            self.get_color(background_code),
+            self.get_color(ColorCode::ButtonBackgroundHover),
+            coef,
+        );
+        let fore = color_blend(
+            self.get_color(foreground_code),
+            self.get_color(ColorCode::ButtonTextHover),
+            coef,
+        );
+        (back, fore)
+    }
+}
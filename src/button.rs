@@ -1,6 +1,9 @@
 use std::collections::HashSet;
 
-use crate::{ColorCode, Context, Coord, DeferedCommand, Id, TextAlign, MOUSE_BUTTON_LEFT};
+use crate::{
+    ColorCode, Command, Context, Coord, DeferedCommand, Id, Layer, Pos, RadioState, Rect,
+    SpecialKey, TextAlign, MOUSE_BUTTON_LEFT, NULL_ID,
+};
 
 impl Context {
     // =======================================================
@@ -15,16 +18,14 @@ impl Context {
         self.update_control(id, &r, false);
         let focus = self.focus == id;
         let hover = self.hover == id;
-        self.pressed = hover && self.mouse_pressed == MOUSE_BUTTON_LEFT;
-        let (background_code, foreground_code) = if hover {
-            (ColorCode::ButtonBackgroundHover, ColorCode::ButtonTextHover)
-        } else if focus {
+        self.pressed =
+            (hover && self.mouse_pressed & MOUSE_BUTTON_LEFT != 0) || self.key_activated(id);
+        let codes = if focus {
             (ColorCode::ButtonBackgroundFocus, ColorCode::ButtonTextFocus)
         } else {
             (ColorCode::ButtonBackground, ColorCode::ButtonText)
         };
-        let back = self.get_color(background_code);
-        let fore = self.get_color(foreground_code);
+        let (back, fore) = self.hover_colors(id, hover, codes);
         self.defered(DeferedCommand::Button(label.to_owned(), back, fore));
         //println!("{}: {} {} {}",id, focus,hover,pressed);
         self
@@ -64,8 +65,10 @@ impl Context {
         ids.insert(id);
     }
     fn disable_toggle_group(&mut self, group: usize) {
-        for id in self.toggle_group.get(&group).unwrap() {
-            self.button_state.insert(*id, 0);
+        if let Some(ids) = self.toggle_group.get(&group) {
+            for id in ids {
+                self.button_state.insert(*id, 0);
+            }
         }
     }
     pub fn toggle_group(&mut self, group: usize) {
@@ -80,7 +83,8 @@ impl Context {
         self.update_control(id, &r, false);
         let focus = self.focus == id;
         let hover = self.hover == id;
-        let pressed = hover && self.mouse_pressed == MOUSE_BUTTON_LEFT;
+        let pressed =
+            (hover && self.mouse_pressed & MOUSE_BUTTON_LEFT != 0) || self.key_activated(id);
         let mut on = *self
             .button_state
             .get(&self.last_id)
@@ -112,6 +116,95 @@ impl Context {
             .insert(toggle_id, if status { 1 } else { 0 });
     }
 
+    // =======================================================
+    //
+    // Radio buttons
+    //
+    // =======================================================
+
+    /// starts a group of mutually exclusive radio buttons.
+    /// default is the index of the button selected the first time the group is displayed.
+    /// When a radio button has the focus, the arrow keys select the previous/next one.
+    pub fn radio_begin(&mut self, id: &str, default: usize) {
+        self.try_commit();
+        let group_id = self.generate_id(id);
+        self.prefix_id(id);
+        self.radio_group = group_id;
+        self.radio_changed = false;
+        let focus = self.focus;
+        let state = self.radio_state.entry(group_id).or_insert(RadioState {
+            selected: default,
+            items: Vec::new(),
+        });
+        let count = state.items.len();
+        let focused = state.items.iter().position(|id| *id == focus);
+        let items = std::mem::take(&mut state.items);
+        if let Some(index) = focused {
+            let mut selected = index;
+            if self.take_special_key(SpecialKey::Up) || self.take_special_key(SpecialKey::Left) {
+                selected = (index + count - 1) % count;
+            } else if self.take_special_key(SpecialKey::Down)
+                || self.take_special_key(SpecialKey::Right)
+            {
+                selected = (index + 1) % count;
+            }
+            if selected != index {
+                self.set_focus(items[selected]);
+                self.select_radio(selected);
+            }
+        }
+    }
+    /// adds a radio button to the group. Returns true if it's the selected one
+    pub fn radio(&mut self, label: &str) -> bool {
+        let group_id = self.radio_group;
+        let index = self
+            .radio_state
+            .get(&group_id)
+            .expect("radio must be called inside radio_begin/radio_end")
+            .items
+            .len();
+        let padded_label = "  ".to_owned() + label;
+        let pressed = self
+            .button(&index.to_string(), &padded_label)
+            .align(TextAlign::Left)
+            .pressed();
+        let id = self.last_id;
+        if pressed {
+            self.select_radio(index);
+        }
+        let state = self.radio_state.get_mut(&group_id).unwrap();
+        state.items.push(id);
+        let selected = state.selected == index;
+        let fore = self.get_color(ColorCode::Text);
+        self.defered(DeferedCommand::Radio(selected, fore));
+        self.active = selected;
+        selected
+    }
+    /// ends the group. Returns the index of the selected button if it has changed this frame
+    pub fn radio_end(&mut self) -> Option<usize> {
+        self.try_commit();
+        self.id_prefix.pop();
+        let changed = std::mem::take(&mut self.radio_changed);
+        let group_id = std::mem::replace(&mut self.radio_group, NULL_ID);
+        if changed {
+            self.radio_state.get(&group_id).map(|state| state.selected)
+        } else {
+            None
+        }
+    }
+    /// change the selected button of a radio group
+    pub fn set_radio_selected(&mut self, group_id: Id, index: usize) {
+        self.radio_state.entry(group_id).or_default().selected = index;
+    }
+    fn select_radio(&mut self, index: usize) {
+        if let Some(state) = self.radio_state.get_mut(&self.radio_group) {
+            if state.selected != index {
+                state.selected = index;
+                self.radio_changed = true;
+            }
+        }
+    }
+
     // =======================================================
     //
     // List button
@@ -159,7 +252,8 @@ impl Context {
         self.update_control(list_button_id, &r, false);
         let focus = self.focus == list_button_id;
         let hover = self.hover == list_button_id;
-        let pressed = hover && self.mouse_pressed == MOUSE_BUTTON_LEFT;
+        let pressed = (hover && self.mouse_pressed & MOUSE_BUTTON_LEFT != 0)
+            || self.key_activated(list_button_id);
         //println!("{}: {} {} {}",list_button_id, focus,hover,pressed);
         let cur_index = *self.button_state.get(&list_button_id).unwrap();
         if pressed {
@@ -194,4 +288,152 @@ impl Context {
         self.draw_text(r, &label, self.list_button_align, fore);
         pressed
     }
+
+    // =======================================================
+    //
+    // Combo box
+    //
+    // =======================================================
+
+    /// a button that opens a drop-down list of values when clicked
+    pub fn combo_begin(&mut self, id: &str, default_value: i32) {
+        self.try_commit();
+        let id = self.generate_id(id);
+        self.combo_items.clear();
+        self.button_state.entry(id).or_insert(default_value);
+    }
+
+    /// add a new item in the list of values
+    /// returns true if this is the current value
+    pub fn combo_item(&mut self, label: &str) -> bool {
+        let combo_id = self.last_id();
+        assert!(
+            self.button_state.contains_key(&combo_id),
+            "combo_item must be called inside combo_begin/combo_end"
+        );
+        self.combo_items.push(label.to_owned());
+        self.button_state[&combo_id] == self.combo_items.len() as i32 - 1
+    }
+
+    /// end the value list.
+    /// returns true if the current value has changed this frame
+    pub fn combo_end(&mut self) -> bool {
+        let combo_id = self.last_id();
+        assert!(
+            self.button_state.contains_key(&combo_id),
+            "combo_end must be called after combo_begin"
+        );
+        let width = self
+            .combo_items
+            .iter()
+            .map(|label| label.chars().count() as Coord)
+            .max()
+            .unwrap_or(0)
+            + 2;
+        let r = self.next_rectangle(width, 1);
+        self.update_control(combo_id, &r, false);
+        let focus = self.focus == combo_id;
+        let hover = self.hover == combo_id;
+        let old_index = self.button_state[&combo_id];
+        let mut cur_index = old_index;
+        let popup = Rect::new(r.x, r.y + 1, r.w, self.combo_items.len() as Coord);
+        let open = if self.combo_open == combo_id {
+            self.update_combo_list(popup, &mut cur_index)
+        } else if (hover && self.mouse_pressed & MOUSE_BUTTON_LEFT != 0)
+            || self.key_activated(combo_id)
+        {
+            self.combo_highlight = cur_index;
+            true
+        } else {
+            false
+        };
+        if open {
+            self.combo_open = combo_id;
+        } else if self.combo_open == combo_id {
+            self.combo_open = NULL_ID;
+        }
+        self.button_state.insert(combo_id, cur_index);
+        let codes = if focus {
+            (ColorCode::ButtonBackgroundFocus, ColorCode::ButtonTextFocus)
+        } else {
+            (ColorCode::ButtonBackground, ColorCode::ButtonText)
+        };
+        let (back, fore) = self.hover_colors(combo_id, hover || open, codes);
+        self.draw_rect(r, back);
+        let label = self
+            .combo_items
+            .get(cur_index as usize)
+            .cloned()
+            .unwrap_or_default();
+        let align = self.next_align.take().unwrap_or(TextAlign::Left);
+        self.draw_text(Rect::new(r.x, r.y, r.w - 2, 1), &label, align, fore);
+        self.push_command(Command::DropDown(
+            Pos {
+                x: r.x + r.w - 1,
+                y: r.y,
+            },
+            open,
+            fore,
+        ));
+        if open {
+            self.draw_combo_list(popup);
+        }
+        cur_index != old_index
+    }
+
+    /// handles keyboard and mouse input of an open combo box list.
+    /// returns false if the list should be closed
+    fn update_combo_list(&mut self, popup: Rect, index: &mut i32) -> bool {
+        let count = self.combo_items.len() as i32;
+        self.layer_areas.push((Layer::Popup, popup));
+        let mouse_pos: Pos = self.mouse_pos.into();
+        if popup.contains(mouse_pos) {
+            self.combo_highlight = mouse_pos.y - popup.y;
+        }
+        let mut open = count > 0;
+        let keys: Vec<SpecialKey> = self.special_keys.drain(0..).collect();
+        for k in keys {
+            match k {
+                SpecialKey::Up if count > 0 => {
+                    self.combo_highlight = (self.combo_highlight + count - 1) % count
+                }
+                SpecialKey::Down if count > 0 => {
+                    self.combo_highlight = (self.combo_highlight + 1) % count
+                }
+                SpecialKey::Enter | SpecialKey::Space => {
+                    *index = self.combo_highlight;
+                    open = false;
+                }
+                SpecialKey::Escape => open = false,
+                _ => self.special_keys.push(k),
+            }
+        }
+        if self.mouse_pressed != 0 {
+            // a click inside the list selects an item, a click anywhere else closes it
+            if popup.contains(mouse_pos) && self.mouse_pressed & MOUSE_BUTTON_LEFT != 0 {
+                *index = self.combo_highlight;
+            }
+            open = false;
+        }
+        open
+    }
+
+    fn draw_combo_list(&mut self, popup: Rect) {
+        self.push_layer(Layer::Popup);
+        let back = self.get_color(ColorCode::ButtonBackground);
+        let back_highlight = self.get_color(ColorCode::ButtonBackgroundHover);
+        let fore = self.get_color(ColorCode::ButtonText);
+        let fore_highlight = self.get_color(ColorCode::ButtonTextHover);
+        self.draw_rect(popup, back);
+        for (i, label) in self.combo_items.clone().iter().enumerate() {
+            let item = Rect::new(popup.x, popup.y + i as Coord, popup.w, 1);
+            if i as i32 == self.combo_highlight {
+                self.draw_rect(item, back_highlight);
+                self.draw_text(item, label, TextAlign::Left, fore_highlight);
+            } else {
+                self.draw_text(item, label, TextAlign::Left, fore);
+            }
+        }
+        self.pop_layer();
+    }
 }
@@ -0,0 +1,29 @@
+/// access to the system clipboard used by the textbox copy/cut/paste.
+/// Set your own implementation with `Context::set_clipboard`
+pub trait Clipboard {
+    fn get_text(&mut self) -> Option<String>;
+    fn set_text(&mut self, text: &str);
+}
+
+/// default clipboard, only shared between the widgets of a context
+#[derive(Default)]
+pub struct MemoryClipboard {
+    text: Option<String>,
+}
+
+impl Clipboard for MemoryClipboard {
+    fn get_text(&mut self) -> Option<String> {
+        self.text.clone()
+    }
+    fn set_text(&mut self, text: &str) {
+        self.text = Some(text.to_owned());
+    }
+}
+
+pub(crate) struct ClipboardBox(pub Box<dyn Clipboard>);
+
+impl Default for ClipboardBox {
+    fn default() -> Self {
+        Self(Box::new(MemoryClipboard::default()))
+    }
+}
@@ -13,6 +13,7 @@ pub enum ColorCode {
     ProgressFore,
     ProgressText,
     Text,
+    TextSelection,
 }
 
 impl Into<usize> for ColorCode {
@@ -31,6 +32,7 @@ impl Into<usize> for ColorCode {
             ColorCode::ProgressBack => COLOR_PROGRESS_BACK,
             ColorCode::ProgressFore => COLOR_PROGRESS_FORE,
             ColorCode::ProgressText => COLOR_PROGRESS_TEXT,
+            ColorCode::TextSelection => COLOR_TEXT_SELECTION,
         }
     }
 }
@@ -49,7 +51,8 @@ const COLOR_TEXT: usize = 9;
 const COLOR_PROGRESS_BACK: usize = 10;
 const COLOR_PROGRESS_FORE: usize = 11;
 const COLOR_PROGRESS_TEXT: usize = 12;
-const COLOR_COUNT: usize = 13;
+const COLOR_TEXT_SELECTION: usize = 13;
+const COLOR_COUNT: usize = 14;
 
 pub struct ColorManager {
     colors: [Vec<Color>; COLOR_COUNT],
@@ -72,6 +75,7 @@ impl Default for ColorManager {
                 vec![(50, 50, 154, 255)],
                 vec![(120, 120, 200, 255)],
                 vec![(180, 180, 220, 255)],
+                vec![(80, 120, 200, 255)],
             ],
         }
     }
@@ -91,3 +95,14 @@ impl ColorManager {
         *self.colors[idx].last().unwrap()
     }
 }
+
+/// linear interpolation between two colors. coef 0.0 returns c1, 1.0 returns c2
+pub(crate) fn color_blend(c1: Color, c2: Color, coef: f32) -> Color {
+    let blend = |a: u8, b: u8| (a as f32 + (b as f32 - a as f32) * coef) as u8;
+    (
+        blend(c1.0, c2.0),
+        blend(c1.1, c2.1),
+        blend(c1.2, c2.2),
+        blend(c1.3, c2.3),
+    )
+}
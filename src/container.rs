@@ -1,4 +1,16 @@
-use crate::{ColorCode, Context, Coord, DeferedCommand, LayoutMode, TextAlign};
+use crate::{
+    derived_id, ColorCode, Command, Context, Coord, DeferedCommand, Easing, Id, LayoutMode, Pos,
+    Rect, SpecialKey, TextAlign, MOUSE_BUTTON_LEFT,
+};
+
+/// number of lines scrolled by a mouse wheel notch
+const SCROLL_WHEEL_STEP: Coord = 3;
+/// duration of the smooth scrolling in seconds
+const SCROLL_ANIM_TIME: f32 = 0.1;
+/// duration of the dropdown panels opening/closing animation in seconds
+const PANEL_ANIM_TIME: f32 = 0.15;
+/// minimum width and height of a resized frame window
+const MIN_WINDOW_SIZE: Coord = 3;
 
 impl Context {
     // =======================================================
@@ -45,7 +57,10 @@ impl Context {
     pub fn flexgrid_end(&mut self) {
         self.end_container();
     }
-    /// The window behaves like a vbox, but it resets the cursor position
+    /// The window behaves like a vbox, but it resets the cursor position.
+    /// Its content is clipped to the window area, unless width or height is 0.
+    /// x,y,width,height are used again only when they change between two frames.
+    /// Windows are drawn above the other widgets, the last clicked window on top.
     pub fn window_begin(
         &mut self,
         id: &str,
@@ -54,10 +69,22 @@ impl Context {
         width: Coord,
         height: Coord,
     ) -> &mut Self {
-        self.vbox_begin(id, height).fixed_pos(x, y, width, height)
+        self.try_commit();
+        let window_id = self.generate_id(id);
+        let r = self.begin_window(window_id, Rect::new(x, y, width, height));
+        self.vbox_begin(id, r.h).fixed_pos(r.x, r.y, r.w, r.h);
+        if r.w > 0 && r.h > 0 {
+            self.defered(DeferedCommand::Clip);
+        }
+        self
     }
     pub fn window_end(&mut self) {
         self.vbox_end();
+        self.end_window();
+    }
+    /// change the position and size of a window
+    pub fn set_window_rect(&mut self, window_id: Id, r: Rect) {
+        self.window_rect.insert(window_id, r);
     }
     /// A vbox with a hide/show header button.
     pub fn dropdown_panel_begin(
@@ -94,16 +121,28 @@ impl Context {
             ColorCode::ButtonText
         });
         self.defered(DeferedCommand::DropDown(on, fore));
-        self.vbox_begin(id, if on { height } else { 0 })
-            .min_width(width);
-        self.active = on;
+        // the panel height is animated when it opens or closes
+        let shown = self
+            .tween(
+                derived_id(button_id, "panel"),
+                if on { height as f32 } else { 0.0 },
+                PANEL_ANIM_TIME,
+                Easing::EaseInOut,
+            )
+            .round() as Coord;
+        self.vbox_begin(id, shown)
+            .min_width(width)
+            .defered(DeferedCommand::Clip);
+        self.active = on || shown > 0;
         self.pressed = pressed;
         self
     }
     pub fn dropdown_panel_end(&mut self) {
         self.vbox_end();
     }
-    /// the frame_window behaves like a frame, but it resets the cursor position
+    /// the frame_window behaves like a frame, but it resets the cursor position.
+    /// It's a floating window that can be moved with its title bar
+    /// and resized with its bottom right corner.
     pub fn frame_window_begin(
         &mut self,
         id: &str,
@@ -113,11 +152,16 @@ impl Context {
         width: Coord,
         height: Coord,
     ) -> &mut Self {
-        self.frame_begin(id, title, width, height)
-            .fixed_pos(x, y, width, height)
+        self.try_commit();
+        let window_id = self.generate_id(id);
+        let r = self.begin_window(window_id, Rect::new(x, y, width, height));
+        let r = self.update_window_move_resize(id, window_id, r);
+        self.frame_begin(id, title, r.w, r.h)
+            .fixed_pos(r.x, r.y, r.w, r.h)
     }
     pub fn frame_window_end(&mut self) {
         self.frame_end();
+        self.end_window();
     }
     /// starts a new vertical container
     ///
@@ -162,7 +206,8 @@ impl Context {
     pub fn hbox_end(&mut self) {
         self.end_container();
     }
-    /// a frame behaves like a vbox with a drawn border and a title
+    /// a frame behaves like a vbox with a drawn border and a title.
+    /// Its content is clipped to the area inside the border
     pub fn frame_begin(&mut self, id: &str, title: &str, width: Coord, height: Coord) -> &mut Self {
         let back = self.get_color(ColorCode::Background);
         let fore = self.get_color(ColorCode::Text);
@@ -177,6 +222,7 @@ impl Context {
         let r = layout.area();
         match layout.defered_iter().next() {
             Some(DeferedCommand::Frame(title, col, coltxt)) => {
+                self.pop_clip();
                 self.render_frame(&title, *col, *coltxt, r)
             }
             Some(c) => panic!(
@@ -204,4 +250,345 @@ impl Context {
         self.frame_window_end();
         ret
     }
+    /// a vbox showing only a width x height part of its content.
+    /// The content can be scrolled with the scrollbars or the mouse wheel.
+    /// A horizontal scrollbar is displayed if the content is wider than the container.
+    pub fn scroll_begin(&mut self, id: &str, width: Coord, height: Coord) -> &mut Self {
+        self.try_commit();
+        let scroll_id = self.generate_id(id);
+        let offset = self.scroll_state.entry(scroll_id).or_default().offset;
+        let shown = Pos {
+            x: offset.x,
+            y: self
+                .tween(
+                    derived_id(scroll_id, "scroll"),
+                    offset.y as f32,
+                    SCROLL_ANIM_TIME,
+                    Easing::EaseOut,
+                )
+                .round() as Coord,
+        };
+        self.scroll_state.get_mut(&scroll_id).unwrap().shown = shown;
+        self.prefix_id(id);
+        self.new_layout(LayoutMode::Vertical)
+            .size(width, height)
+            .min_width(width - 1)
+            .defered(DeferedCommand::Scroll(scroll_id));
+        if let Some(layout) = self.next_layout.as_mut() {
+            layout.move_cursor(-shown.x, -shown.y);
+        }
+        self
+    }
+    pub fn scroll_end(&mut self) {
+        self.try_commit();
+        let mut layout = self.layouts.pop().unwrap();
+        let r = layout.area();
+        let id = match layout.defered_iter().next() {
+            Some(DeferedCommand::Scroll(id)) => *id,
+            Some(c) => panic!(
+                "unmatched begin/end calls. Expected Scroll instead of {:?}",
+                c
+            ),
+            None => panic!("unmatched begin/end calls"),
+        };
+        self.pop_clip();
+        let (mut offset, shown, viewport, command_start) = {
+            let state = &self.scroll_state[&id];
+            (
+                state.offset,
+                state.shown,
+                state.viewport,
+                state.command_start,
+            )
+        };
+        let mut content = Pos::default();
+        let layer = self.current_layer();
+        for (_, c) in self.commands[command_start..]
+            .iter()
+            .filter(|(l, _)| *l == layer)
+        {
+            if let Command::PopClip = c {
+                continue;
+            }
+            let area = c.area();
+            content.x = content.x.max(area.x + area.w - viewport.x + shown.x);
+            content.y = content.y.max(area.y + area.h - viewport.y + shown.y);
+        }
+        let mouse_pos = self.mouse_pos.into();
+        if self.mouse_wheel != 0
+            && r.contains(mouse_pos)
+            && self.clip_contains(mouse_pos)
+            && !self.is_covered(mouse_pos)
+        {
+            offset.y -= self.mouse_wheel * SCROLL_WHEEL_STEP;
+            self.mouse_wheel = 0;
+        }
+        offset.y = offset.y.clamp(0, (content.y - viewport.h).max(0));
+        let vtrack = Rect::new(r.x + r.w - 1, r.y, 1, viewport.h);
+        let dragged = self.scrollbar("vscroll", vtrack, true, offset.y, content.y, viewport.h);
+        if dragged != offset.y {
+            // the scrollbar moves the content without animation
+            self.snap_tween(derived_id(id, "scroll"), dragged as f32);
+            offset.y = dragged;
+        }
+        if viewport.h < r.h {
+            let htrack = Rect::new(r.x, r.y + r.h - 1, viewport.w, 1);
+            offset.x = self.scrollbar("hscroll", htrack, false, offset.x, content.x, viewport.w);
+        } else {
+            offset.x = 0;
+        }
+        // the animation toward the new offset starts now
+        self.tween(
+            derived_id(id, "scroll"),
+            offset.y as f32,
+            SCROLL_ANIM_TIME,
+            Easing::EaseOut,
+        );
+        let state = self.scroll_state.get_mut(&id).unwrap();
+        state.offset = offset;
+        state.content = content;
+        self.id_prefix.pop();
+    }
+    /// scroll to a given position
+    pub fn set_scroll_offset(&mut self, scroll_id: Id, x: Coord, y: Coord) {
+        self.scroll_state.entry(scroll_id).or_default().offset = Pos { x, y };
+    }
+    // =======================================================
+    //
+    // Tabs
+    //
+    // =======================================================
+    /// a framed panel with a tab strip on top.
+    /// Call tab() for each page and only build the content of the page for which it returns true.
+    /// When the strip has the focus, Left/Right select the previous/next tab.
+    /// Like frame_begin, it needs a width and a height because the layouts
+    /// cannot size a container from its content.
+    pub fn tabs_begin(&mut self, id: &str, width: Coord, height: Coord) -> &mut Self {
+        self.try_commit();
+        let tabs_id = self.generate_id(id);
+        self.vbox_begin(id, height).fixed_size(width, height);
+        self.try_commit();
+        let strip = self.next_rectangle(width, 1);
+        self.update_control(tabs_id, &strip, false);
+        self.update_tabs_state(tabs_id, strip);
+        self.tabs_stack.push(tabs_id);
+        self.frame_begin("tabs_body", "", width, height - 1)
+    }
+    /// adds a tab. returns true if it's the selected one
+    pub fn tab(&mut self, label: &str) -> bool {
+        let tabs_id = *self
+            .tabs_stack
+            .last()
+            .expect("tab() called outside tabs_begin/end");
+        let state = self.tabs_state.get_mut(&tabs_id).unwrap();
+        state.labels.push(label.to_owned());
+        state.labels.len() - 1 == state.selected
+    }
+    /// returns the index of the selected tab
+    pub fn tabs_end(&mut self) -> usize {
+        self.frame_end();
+        self.vbox_end();
+        let tabs_id = self.tabs_stack.pop().unwrap();
+        let hover = self.hover == tabs_id;
+        let mouse_pos: Pos = self.mouse_pos.into();
+        let state = self.tabs_state.get_mut(&tabs_id).unwrap();
+        let strip = state.strip;
+        let selected = state.selected.min(state.labels.len().saturating_sub(1));
+        state.selected = selected;
+        // the first tab starts after the frame corner
+        let mut x = strip.x + 1;
+        state.rects.clear();
+        for label in state.labels.iter() {
+            let w = label.chars().count() as Coord + 2;
+            state.rects.push(Rect::new(x, strip.y, w, 1));
+            x += w;
+        }
+        let tabs: Vec<(Rect, String)> = state
+            .rects
+            .iter()
+            .copied()
+            .zip(state.labels.iter().cloned())
+            .collect();
+        for (i, (r, label)) in tabs.iter().enumerate() {
+            let (back_code, fore_code) = if i == selected {
+                (ColorCode::Background, ColorCode::Text)
+            } else if hover && r.contains(mouse_pos) {
+                (ColorCode::ButtonBackgroundHover, ColorCode::ButtonTextHover)
+            } else {
+                (ColorCode::ButtonBackground, ColorCode::ButtonText)
+            };
+            let back = self.get_color(back_code);
+            let fore = self.get_color(fore_code);
+            self.draw_rect(*r, back);
+            self.draw_text(*r, label, TextAlign::Center, fore);
+            if i == selected {
+                // open the frame border below the selected tab
+                let gap = Rect::new(r.x, r.y + 1, r.w, 1);
+                self.draw_rect(gap, back);
+                self.draw_text(gap, &" ".repeat(r.w as usize), TextAlign::Left, fore);
+            }
+        }
+        selected
+    }
+    /// select a tab by code
+    pub fn set_selected_tab(&mut self, tabs_id: Id, index: usize) {
+        self.tabs_state.entry(tabs_id).or_default().selected = index;
+    }
+    /// handles the clicks on the tab strip and the keyboard using the previous frame's tabs
+    fn update_tabs_state(&mut self, tabs_id: Id, strip: Rect) {
+        let focus = self.focus == tabs_id;
+        let clicked = self.hover == tabs_id && self.mouse_pressed & MOUSE_BUTTON_LEFT != 0;
+        let mouse_pos: Pos = self.mouse_pos.into();
+        let state = self.tabs_state.entry(tabs_id).or_default();
+        let count = state.labels.len();
+        state.strip = strip;
+        state.labels.clear();
+        if clicked {
+            if let Some(index) = state.rects.iter().position(|r| r.contains(mouse_pos)) {
+                state.selected = index;
+            }
+        }
+        if focus && count > 0 {
+            let selected = &mut state.selected;
+            self.special_keys.retain(|k| match k {
+                SpecialKey::Left => {
+                    *selected = (*selected + count - 1) % count;
+                    false
+                }
+                SpecialKey::Right => {
+                    *selected = (*selected + 1) % count;
+                    false
+                }
+                _ => true,
+            });
+        }
+    }
+    pub(crate) fn start_scroll(&mut self, id: Id, r: Rect) {
+        let state = self.scroll_state.entry(id).or_default();
+        let hbar = state.content.x > r.w - 1;
+        state.viewport = Rect::new(r.x, r.y, r.w - 1, if hbar { r.h - 1 } else { r.h });
+        let viewport = state.viewport;
+        self.push_clip(viewport);
+        // content size is computed from the commands following the PushClip
+        let command_start = self.commands.len();
+        self.scroll_state.get_mut(&id).unwrap().command_start = command_start;
+    }
+    /// draws a scrollbar and returns the new offset
+    pub(crate) fn scrollbar(
+        &mut self,
+        name: &str,
+        track: Rect,
+        vertical: bool,
+        offset: Coord,
+        content: Coord,
+        view: Coord,
+    ) -> Coord {
+        let id = self.generate_id(name);
+        let max_offset = (content - view).max(0);
+        let (track_start, track_len, mouse, dnd_start) = if vertical {
+            (track.y, track.h, self.mouse_pos.1, self.dnd_start.1)
+        } else {
+            (track.x, track.w, self.mouse_pos.0, self.dnd_start.0)
+        };
+        let handle_len = if max_offset == 0 {
+            track_len
+        } else {
+            (track_len * view / content).max(1)
+        };
+        let free_len = (track_len - handle_len).max(1);
+        let was_focus = self.focus == id;
+        self.update_mouse_control(id, &track, true);
+        let focus = self.focus == id;
+        let hover = self.hover == id;
+        let mut offset = offset.clamp(0, max_offset);
+        if focus && self.mouse_down & MOUSE_BUTTON_LEFT != 0 && max_offset > 0 {
+            if !self.dnd_on {
+                // clicking the track outside of the handle moves the handle under the mouse
+                let handle_start = track_start + offset * free_len / max_offset;
+                let pos = mouse as Coord;
+                if pos < handle_start || pos >= handle_start + handle_len {
+                    offset = (pos - track_start - handle_len / 2) * max_offset / free_len;
+                }
+                self.start_dnd(offset as f32);
+            } else {
+                let delta = mouse - dnd_start;
+                offset = (self.dnd_value + delta * max_offset as f32 / free_len as f32) as Coord;
+            }
+            offset = offset.clamp(0, max_offset);
+        } else if was_focus {
+            self.dnd_on = false;
+        }
+        let handle_start = if max_offset == 0 {
+            0
+        } else {
+            offset * free_len / max_offset
+        };
+        let handle = if vertical {
+            Rect::new(track.x, track.y + handle_start, 1, handle_len)
+        } else {
+            Rect::new(track.x + handle_start, track.y, handle_len, 1)
+        };
+        let back = self.get_color(ColorCode::ButtonBackground);
+        let fore = self.get_color(if focus || hover {
+            ColorCode::ButtonTextHover
+        } else {
+            ColorCode::Text
+        });
+        self.draw_rect(track, back);
+        self.draw_rect(handle, fore);
+        offset
+    }
+    fn begin_window(&mut self, window_id: Id, r: Rect) -> Rect {
+        if !self.window_order.contains(&window_id) {
+            self.window_order.push(window_id);
+        }
+        self.drawn_windows.push(window_id);
+        self.window_stack.push(window_id);
+        self.push_layer(self.window_layer(window_id));
+        // the caller moved the window, for example to center it on a resized screen
+        if self.window_request.insert(window_id, r) != Some(r) {
+            self.window_rect.insert(window_id, r);
+        }
+        self.window_rect[&window_id]
+    }
+    fn end_window(&mut self) {
+        self.window_stack.pop();
+        self.pop_layer();
+    }
+    /// drag the title bar to move the window, drag the bottom right corner to resize it
+    fn update_window_move_resize(&mut self, id: &str, window_id: Id, r: Rect) -> Rect {
+        let move_id = self.generate_id(&format!("{}#move", id));
+        let resize_id = self.generate_id(&format!("{}#resize", id));
+        self.last_id = window_id;
+        let title_bar = Rect::new(r.x, r.y, r.w, 1);
+        let corner = Rect::new(r.x + r.w - 1, r.y + r.h - 1, 1, 1);
+        let mut new_rect = r;
+        for (control_id, area) in [(move_id, title_bar), (resize_id, corner)].iter() {
+            let was_focus = self.focus == *control_id;
+            self.update_mouse_control(*control_id, area, true);
+            if self.focus == *control_id && self.mouse_down & MOUSE_BUTTON_LEFT != 0 {
+                if !self.dnd_on {
+                    self.start_dnd(0.0);
+                    self.dnd_rect = r;
+                } else {
+                    let dx = (self.mouse_pos.0 - self.dnd_start.0) as Coord;
+                    let dy = (self.mouse_pos.1 - self.dnd_start.1) as Coord;
+                    new_rect = if *control_id == move_id {
+                        Rect::new(self.dnd_rect.x + dx, self.dnd_rect.y + dy, r.w, r.h)
+                    } else {
+                        Rect::new(
+                            r.x,
+                            r.y,
+                            (self.dnd_rect.w + dx).max(MIN_WINDOW_SIZE),
+                            (self.dnd_rect.h + dy).max(MIN_WINDOW_SIZE),
+                        )
+                    };
+                }
+            } else if was_focus {
+                self.dnd_on = false;
+            }
+        }
+        self.window_rect.insert(window_id, new_rect);
+        new_rect
+    }
 }
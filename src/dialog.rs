@@ -0,0 +1,179 @@
+use crate::{Color, Context, Coord, Layer, Rect, SpecialKey, NULL_ID};
+
+/// answer of a dialog. Dialogs with a single "Ok" button return Yes,
+/// Escape returns Cancel, or No if the dialog has a No button but no Cancel button.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum DialogResult {
+    Yes,
+    No,
+    Cancel,
+}
+
+const PROMPT_WIDTH: Coord = 20;
+
+impl Context {
+    // =======================================================
+    //
+    // Modal dialogs
+    //
+    // =======================================================
+    /// starts a frame centered on the screen. While it is open,
+    /// all the widgets outside of it ignore the mouse and the keyboard.
+    /// The screen size must be known (see input_screen_size) for the dialog to be centered.
+    pub fn modal_begin(&mut self, id: &str, title: &str, width: Coord, height: Coord) -> &mut Self {
+        self.try_commit();
+        self.modal_depth += 1;
+        self.modal_open = true;
+        self.push_layer(Layer::Modal);
+        let (screen_width, screen_height) = self.screen_size;
+        if let Some(col) = self.modal_dim {
+            self.draw_rect(Rect::new(0, 0, screen_width, screen_height), col);
+        }
+        let x = ((screen_width - width) / 2).max(0);
+        let y = ((screen_height - height) / 2).max(0);
+        self.frame_begin(id, title, width, height)
+            .fixed_pos(x, y, width, height)
+    }
+    pub fn modal_end(&mut self) {
+        self.frame_end();
+        self.pop_layer();
+        self.modal_depth -= 1;
+    }
+    /// color of the rectangle drawn over the whole screen behind the modal dialogs.
+    /// None (the default) disables the dimming.
+    pub fn set_modal_dimming(&mut self, col: Option<Color>) {
+        self.modal_dim = col;
+    }
+    /// a message with an "Ok" button. Call it every frame until it returns Some
+    pub fn message_box(&mut self, id: &str, title: &str, text: &str) -> Option<DialogResult> {
+        self.dialog(id, title, text, &[("Ok", DialogResult::Yes)], None)
+    }
+    /// a question with "Yes" and "No" buttons. Call it every frame until it returns Some
+    pub fn confirm(&mut self, id: &str, title: &str, text: &str) -> Option<DialogResult> {
+        self.dialog(
+            id,
+            title,
+            text,
+            &[("Yes", DialogResult::Yes), ("No", DialogResult::No)],
+            None,
+        )
+    }
+    /// a question with "Yes", "No" and "Cancel" buttons. Call it every frame until it returns Some
+    pub fn confirm_cancel(&mut self, id: &str, title: &str, text: &str) -> Option<DialogResult> {
+        self.dialog(
+            id,
+            title,
+            text,
+            &[
+                ("Yes", DialogResult::Yes),
+                ("No", DialogResult::No),
+                ("Cancel", DialogResult::Cancel),
+            ],
+            None,
+        )
+    }
+    /// a question with a textbox and "Ok"/"Cancel" buttons. value is used as the initial
+    /// content of the textbox and receives the text typed by the player.
+    /// Call it every frame until it returns Some
+    pub fn prompt(
+        &mut self,
+        id: &str,
+        title: &str,
+        text: &str,
+        value: &mut String,
+    ) -> Option<DialogResult> {
+        self.dialog(
+            id,
+            title,
+            text,
+            &[("Ok", DialogResult::Yes), ("Cancel", DialogResult::Cancel)],
+            Some(value),
+        )
+    }
+    fn dialog(
+        &mut self,
+        id: &str,
+        title: &str,
+        text: &str,
+        buttons: &[(&str, DialogResult)],
+        value: Option<&mut String>,
+    ) -> Option<DialogResult> {
+        let lines: Vec<&str> = text.lines().collect();
+        let labels: Vec<String> = buttons
+            .iter()
+            .map(|(label, _)| format!(" {} ", label))
+            .collect();
+        let buttons_width = labels
+            .iter()
+            .map(|label| label.chars().count() as Coord + 1)
+            .sum::<Coord>()
+            - 1;
+        let text_width = lines
+            .iter()
+            .map(|line| line.chars().count() as Coord)
+            .max()
+            .unwrap_or(0);
+        let mut width = text_width
+            .max(buttons_width)
+            .max(title.chars().count() as Coord + 2);
+        if value.is_some() {
+            width = width.max(PROMPT_WIDTH);
+        }
+        let prompt_height = if value.is_some() { 1 } else { 0 };
+        let height = lines.len() as Coord + prompt_height + 2;
+        let mut result = None;
+        let mut ids = Vec::new();
+        self.modal_begin(id, title, width + 4, height + 4).margin(1);
+        for line in lines.iter() {
+            self.label(line);
+        }
+        let textbox_id = value.as_ref().map(|value| {
+            let textbox_id = self
+                .textbox("value", width as usize, Some(value.as_str()), None)
+                .last_id();
+            ids.push(textbox_id);
+            textbox_id
+        });
+        self.label("");
+        self.hbox_begin("buttons").hpadding(1);
+        for ((_, answer), label) in buttons.iter().zip(labels.iter()) {
+            let name = label.trim().to_lowercase();
+            if self.button(&name, label).pressed() {
+                result = Some(*answer);
+            }
+            ids.push(self.last_id());
+        }
+        self.hbox_end();
+        self.modal_end();
+        if let Some(textbox_id) = textbox_id {
+            if self.focus == textbox_id && self.take_special_key(SpecialKey::Enter) {
+                result = Some(DialogResult::Yes);
+            }
+        }
+        if self.take_special_key(SpecialKey::Escape) {
+            let has = |answer| buttons.iter().any(|(_, a)| *a == answer);
+            result = Some(if has(DialogResult::No) && !has(DialogResult::Cancel) {
+                DialogResult::No
+            } else {
+                DialogResult::Cancel
+            });
+        }
+        if !ids.contains(&self.focus) {
+            // keyboard focus on the textbox or the first button when the dialog opens
+            self.set_focus(ids[0]);
+        }
+        if let (Some(textbox_id), Some(value)) = (textbox_id, value) {
+            *value = self.text(textbox_id).to_owned();
+            if result.is_some() {
+                // next time the dialog opens, the textbox starts with the new value
+                self.textbox_state.remove(&textbox_id);
+            }
+        }
+        if result.is_some() {
+            self.set_focus(NULL_ID);
+            // the dialog is closed : don't block the input during the next frame
+            self.modal_open = false;
+        }
+        result
+    }
+}
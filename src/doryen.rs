@@ -1,38 +1,89 @@
-use crate::{Command, Context, Coord, Pos, Rect, SpecialKey, MOUSE_BUTTON_LEFT};
-use doryen_rs::{Color, Console, DoryenApi, TextAlign, CHAR_LINE_H};
-
-pub fn text_color_len(txt: &str) -> usize {
-    Console::text_color_len(txt)
-}
+use crate::{
+    Command, Context, Coord, Pos, Rect, SpecialKey, KEY_MOD_ALT, KEY_MOD_CTRL, KEY_MOD_SHIFT,
+    MOUSE_BUTTON_LEFT, MOUSE_BUTTON_MIDDLE, MOUSE_BUTTON_RIGHT,
+};
+use doryen_rs::{Color, Console, DoryenApi, TextAlign, CHAR_LINE_H, CHAR_LINE_V};
 
 pub fn update_doryen_input_data(api: &mut dyn DoryenApi, ctx: &mut Context) {
+    let con = api.con();
+    ctx.input_screen_size(con.get_width() as Coord, con.get_height() as Coord);
     let input = api.input();
     let (mx, my) = input.mouse_pos();
     ctx.input_mouse_pos(mx, my);
-    if input.mouse_button_pressed(0) {
-        ctx.input_mouse_down(MOUSE_BUTTON_LEFT);
-    } else if input.mouse_button_released(0) {
-        ctx.input_mouse_up(MOUSE_BUTTON_LEFT);
+    for (num, button) in [
+        (0, MOUSE_BUTTON_LEFT),
+        (1, MOUSE_BUTTON_MIDDLE),
+        (2, MOUSE_BUTTON_RIGHT),
+    ]
+    .iter()
+    {
+        if input.mouse_button_pressed(*num) {
+            ctx.input_mouse_down(*button);
+        } else if input.mouse_button_released(*num) {
+            ctx.input_mouse_up(*button);
+        }
     }
     let text = input.text();
     ctx.input_text(text);
-    if input.key_pressed("Backspace") {
-        ctx.special_keys.push(SpecialKey::Backspace);
+    let mut modifiers = 0;
+    if input.key("ShiftLeft") || input.key("ShiftRight") {
+        modifiers |= KEY_MOD_SHIFT;
     }
-    if input.key_pressed("Delete") {
-        ctx.special_keys.push(SpecialKey::Delete);
+    if input.key("ControlLeft") || input.key("ControlRight") {
+        modifiers |= KEY_MOD_CTRL;
     }
-    if input.key_pressed("ArrowLeft") {
-        ctx.special_keys.push(SpecialKey::Left);
+    if input.key("AltLeft") || input.key("AltRight") {
+        modifiers |= KEY_MOD_ALT;
     }
-    if input.key_pressed("ArrowRight") {
-        ctx.special_keys.push(SpecialKey::Right);
+    ctx.input_key_modifiers(modifiers);
+    for (key, special_key) in [
+        ("Backspace", SpecialKey::Backspace),
+        ("Delete", SpecialKey::Delete),
+        ("ArrowLeft", SpecialKey::Left),
+        ("ArrowRight", SpecialKey::Right),
+        ("ArrowUp", SpecialKey::Up),
+        ("ArrowDown", SpecialKey::Down),
+        ("Home", SpecialKey::Home),
+        ("End", SpecialKey::End),
+        ("PageUp", SpecialKey::PageUp),
+        ("PageDown", SpecialKey::PageDown),
+        ("Tab", SpecialKey::Tab),
+        ("Enter", SpecialKey::Enter),
+        ("NumpadEnter", SpecialKey::Enter),
+        ("Escape", SpecialKey::Escape),
+        ("Space", SpecialKey::Space),
+    ]
+    .iter()
+    {
+        if input.key_pressed(key) {
+            ctx.input_special_key(*special_key);
+        }
     }
-    if input.key_pressed("Home") {
-        ctx.special_keys.push(SpecialKey::Home);
+    for c in ('a'..='z').chain('0'..='9') {
+        let key = if c.is_ascii_digit() {
+            format!("Digit{}", c)
+        } else {
+            format!("Key{}", c.to_ascii_uppercase())
+        };
+        if input.key_pressed(&key) {
+            ctx.input_char_key(c);
+        }
     }
-    if input.key_pressed("End") {
-        ctx.special_keys.push(SpecialKey::End);
+    if modifiers & KEY_MOD_CTRL != 0 {
+        for (key, special_key) in [
+            ("KeyA", SpecialKey::SelectAll),
+            ("KeyC", SpecialKey::Copy),
+            ("KeyX", SpecialKey::Cut),
+            ("KeyV", SpecialKey::Paste),
+            ("KeyZ", SpecialKey::Undo),
+            ("KeyY", SpecialKey::Redo),
+        ]
+        .iter()
+        {
+            if input.key_pressed(key) {
+                ctx.input_special_key(*special_key);
+            }
+        }
     }
 }
 
@@ -47,26 +98,25 @@ impl From<crate::TextAlign> for TextAlign {
 }
 
 pub fn render_doryen(con: &mut Console, ctx: &mut Context) {
-    for c in ctx.get_render_commands().iter() {
-        match c {
-            Command::Rect(r, col) => render_rect(con, &r, *col),
-            Command::Line(p1, p2, col) => render_line(con, *p1, *p2, *col),
-            Command::Text(txt, pos, col) => render_text(con, *pos, &txt, *col),
-            Command::TextColor(txt, pos, align) => {
-                render_text_color(con, *pos, &txt, (*align).into())
-            }
-            Command::Frame(txt, r, col, coltext) => render_frame(con, &txt, &r, *col, *coltext),
-            Command::CheckBox(pos, checked, col) => {
-                render_checkbox(con, *pos, *checked, *col);
-            }
-            Command::DropDown(pos, open, col) => {
-                render_dropdown(con, *pos, *open, *col);
-            }
-            Command::Progress(r, value, back, fore) => {
-                render_progress(con, *r, *value, *back, *fore);
-            }
+    ctx.visit_visible_commands(|c| match c {
+        Command::Rect(r, col) => render_rect(con, &r, *col),
+        Command::Line(p1, p2, col) => render_line(con, *p1, *p2, *col),
+        Command::Text(txt, pos, col) => render_text(con, *pos, &txt, *col),
+        Command::Frame(txt, r, col, coltext) => render_frame(con, &txt, &r, *col, *coltext),
+        Command::CheckBox(pos, checked, col) => {
+            render_checkbox(con, *pos, *checked, *col);
         }
-    }
+        Command::Radio(pos, selected, col) => {
+            render_radio(con, *pos, *selected, *col);
+        }
+        Command::DropDown(pos, open, col) => {
+            render_dropdown(con, *pos, *open, *col);
+        }
+        Command::Progress(r, value, back, fore) => {
+            render_progress(con, *r, *value, *back, *fore);
+        }
+        Command::PushClip(_) | Command::PopClip => unreachable!(),
+    });
 }
 
 fn render_rect(con: &mut Console, r: &Rect, col: Color) {
@@ -87,26 +137,49 @@ fn render_progress(con: &mut Console, r: Rect, coef: f32, back: Color, fore: Col
     }
 }
 fn render_line(con: &mut Console, p1: Pos, p2: Pos, col: Color) {
+    let vertical = p2.y - p1.y > p2.x - p1.x;
     con.area(
         p1.x,
         p1.y,
-        (p2.x - p1.x) as u32,
-        (p2.y - p1.y) as u32,
+        (p2.x - p1.x).max(1) as u32,
+        (p2.y - p1.y).max(1) as u32,
         Some(col),
         None,
-        Some(CHAR_LINE_H),
+        Some(if vertical { CHAR_LINE_V } else { CHAR_LINE_H }),
     );
 }
 fn render_text(con: &mut Console, pos: Pos, txt: &str, col: Color) {
-    con.print(pos.x, pos.y, txt, TextAlign::Left, Some(col), None);
+    if !txt.chars().any(|c| box_drawing_ascii(c).is_some()) {
+        con.print(pos.x, pos.y, txt, TextAlign::Left, Some(col), None);
+        return;
+    }
+    // box drawing glyphs (tree view guides, clipped frames) are converted to their CP437 code
+    for (i, c) in txt.chars().enumerate() {
+        let x = pos.x + i as Coord;
+        con.ascii(x, pos.y, box_drawing_ascii(c).unwrap_or(c as u16));
+        con.fore(x, pos.y, col);
+    }
 }
-fn render_text_color(con: &mut Console, pos: Pos, txt: &str, align: TextAlign) {
-    con.print_color(pos.x, pos.y, txt, align, None);
+fn box_drawing_ascii(c: char) -> Option<u16> {
+    match c {
+        '│' => Some(179),
+        '├' => Some(195),
+        '└' => Some(192),
+        '┌' => Some(218),
+        '┐' => Some(191),
+        '┘' => Some(217),
+        '─' => Some(196),
+        _ => None,
+    }
 }
 fn render_checkbox(con: &mut Console, pos: Pos, checked: bool, col: Color) {
     con.ascii(pos.x, pos.y, if checked { 225 } else { 224 });
     con.fore(pos.x, pos.y, col);
 }
+fn render_radio(con: &mut Console, pos: Pos, selected: bool, col: Color) {
+    con.ascii(pos.x, pos.y, if selected { 10 } else { 9 });
+    con.fore(pos.x, pos.y, col);
+}
 fn render_dropdown(con: &mut Console, pos: Pos, open: bool, col: Color) {
     con.ascii(pos.x, pos.y, if open { 31 } else { 16 });
     con.fore(pos.x, pos.y, col);
@@ -7,20 +7,42 @@ use std::hash::{Hash, Hasher};
 #[cfg(feature = "doryen")]
 mod doryen;
 
+mod anim;
 mod button;
+mod clipboard;
 mod color;
 mod container;
+mod dialog;
 mod layout;
+mod listbox;
+mod log;
+mod markup;
+mod menu;
 mod slider;
+mod spinner;
+mod table;
+#[cfg(feature = "terminal")]
+mod terminal;
 mod text;
+mod tree;
 
 #[cfg(feature = "doryen")]
 pub use doryen::*;
+#[cfg(feature = "terminal")]
+pub use terminal::*;
 
+pub use anim::Easing;
+pub use clipboard::{Clipboard, MemoryClipboard};
 pub use color::{Color, ColorCode};
+pub use dialog::DialogResult;
+pub use markup::text_color_len;
+pub use spinner::SpinnerValue;
+pub use table::{Column, TableSort};
 
+use clipboard::ClipboardBox;
 use color::*;
 use layout::*;
+use markup::*;
 
 #[derive(Copy, Clone, Debug)]
 pub enum TextAlign {
@@ -37,15 +59,23 @@ impl Default for TextAlign {
 pub type Coord = i32;
 pub type Id = u64;
 const NULL_ID: Id = 0;
+/// time in seconds a widget must be hovered before its tooltip shows
+const TOOLTIP_DELAY: f32 = 0.5;
+/// frame duration when the time is not provided with input_time
+const DEFAULT_FRAME_TIME: f32 = 1.0 / 60.0;
 
 #[derive(Debug, PartialEq, Eq)]
 pub enum DeferedCommand {
     Frame(String, Color, Color),
     Button(String, Color, Color),
     CheckBox(bool, Color),
+    Radio(bool, Color),
     DropDown(bool, Color),
     Label(Rect, String, Color, Color),
-    LabelColor(Rect, String, Color),
+    LabelColor(Rect, String, Color, Color),
+    Paragraph(Rect, Vec<String>, Color, Color),
+    Scroll(Id),
+    Clip,
 }
 
 #[derive(Clone, Copy, Default, Debug, PartialEq, Eq)]
@@ -88,36 +118,173 @@ impl Rect {
     pub fn contains(&self, p: Pos) -> bool {
         p.x >= self.x && p.y >= self.y && p.x < self.x + self.w && p.y < self.y + self.h
     }
+    /// the part of this rectangle that is inside the other one. Might have a zero size
+    pub fn intersection(&self, other: &Rect) -> Rect {
+        let x = self.x.max(other.x);
+        let y = self.y.max(other.y);
+        let x2 = (self.x + self.w).min(other.x + other.w);
+        let y2 = (self.y + self.h).min(other.y + other.h);
+        Rect::new(x, y, (x2 - x).max(0), (y2 - y).max(0))
+    }
+    pub fn is_empty(&self) -> bool {
+        self.w <= 0 || self.h <= 0
+    }
 }
 
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub enum Command {
     Rect(Rect, Color),
     Text(String, Pos, Color),
-    TextColor(String, Pos, TextAlign),
     Frame(String, Rect, Color, Color),
     Line(Pos, Pos, Color),
     CheckBox(Pos, bool, Color),
+    Radio(Pos, bool, Color),
     DropDown(Pos, bool, Color),
     Progress(Rect, f32, Color, Color),
+    /// following commands are only visible inside this rectangle
+    PushClip(Rect),
+    /// restores the clip area of the matching PushClip
+    PopClip,
+}
+
+impl Command {
+    /// the console area touched by this command
+    pub fn area(&self) -> Rect {
+        match self {
+            Command::Rect(r, _)
+            | Command::Frame(_, r, _, _)
+            | Command::Progress(r, _, _, _)
+            | Command::PushClip(r) => *r,
+            Command::PopClip => Rect::default(),
+            Command::Text(txt, pos, _) => Rect::new(pos.x, pos.y, txt.chars().count() as Coord, 1),
+            Command::Line(p1, p2, _) => {
+                Rect::new(p1.x, p1.y, (p2.x - p1.x).max(1), (p2.y - p1.y).max(1))
+            }
+            Command::CheckBox(pos, _, _)
+            | Command::Radio(pos, _, _)
+            | Command::DropDown(pos, _, _) => Rect::new(pos.x, pos.y, 1, 1),
+        }
+    }
+    /// restricts the command to the clip area. Returns the visible pieces of the command,
+    /// nothing if it is entirely outside the clip area
+    pub fn clip(self, clip: &Rect) -> Vec<Command> {
+        let area = self.area();
+        let visible = area.intersection(clip);
+        if visible.is_empty() {
+            return Vec::new();
+        }
+        if visible == area {
+            return vec![self];
+        }
+        match self {
+            Command::Rect(_, col) => vec![Command::Rect(visible, col)],
+            Command::Text(txt, pos, col) => {
+                let txt = txt
+                    .chars()
+                    .skip((visible.x - pos.x) as usize)
+                    .take(visible.w as usize)
+                    .collect::<String>();
+                vec![Command::Text(txt, visible.into(), col)]
+            }
+            Command::Line(p1, p2, col) => {
+                // keeps the orientation of the lines without thickness
+                let x2 = if p2.x == p1.x {
+                    visible.x
+                } else {
+                    visible.x + visible.w
+                };
+                let y2 = if p2.y == p1.y {
+                    visible.y
+                } else {
+                    visible.y + visible.h
+                };
+                vec![Command::Line(visible.into(), Pos { x: x2, y: y2 }, col)]
+            }
+            Command::Progress(r, coef, back, fore) => {
+                let fore_width = r.w as f32 * coef;
+                let coef = ((r.x as f32 + fore_width - visible.x as f32) / visible.w as f32)
+                    .clamp(0.0, 1.0);
+                vec![Command::Progress(visible, coef, back, fore)]
+            }
+            Command::Frame(title, r, col, coltxt) => frame_pieces(&title, r, col, coltxt)
+                .into_iter()
+                .flat_map(|piece| piece.clip(clip))
+                .collect(),
+            // single cell commands are either entirely visible or hidden
+            _ => Vec::new(),
+        }
+    }
+}
+
+/// a frame split into its background, borders, corners and title,
+/// so that a partly visible frame can be clipped
+fn frame_pieces(title: &str, r: Rect, col: Color, coltxt: Color) -> Vec<Command> {
+    let (x2, y2) = (r.x + r.w - 1, r.y + r.h - 1);
+    let corner = |glyph: char, x, y| Command::Text(glyph.to_string(), Pos { x, y }, coltxt);
+    let title_x = r.x + r.w / 2 - title.chars().count() as Coord / 2;
+    vec![
+        Command::Rect(r, col),
+        Command::Line(Pos { x: r.x + 1, y: r.y }, Pos { x: x2, y: r.y }, coltxt),
+        Command::Line(Pos { x: r.x + 1, y: y2 }, Pos { x: x2, y: y2 }, coltxt),
+        Command::Line(Pos { x: r.x, y: r.y + 1 }, Pos { x: r.x, y: y2 }, coltxt),
+        Command::Line(Pos { x: x2, y: r.y + 1 }, Pos { x: x2, y: y2 }, coltxt),
+        corner('┌', r.x, r.y),
+        corner('┐', x2, r.y),
+        corner('└', r.x, y2),
+        corner('┘', x2, y2),
+        Command::Text(title.to_owned(), Pos { x: title_x, y: r.y }, coltxt),
+    ]
 }
 
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
 pub enum SpecialKey {
     Backspace,
     Delete,
     Left,
     Right,
+    Up,
+    Down,
     Home,
     End,
+    PageUp,
+    PageDown,
+    Tab,
+    Enter,
+    Escape,
+    Space,
+    // editing shortcuts, usually Ctrl+A/C/X/V/Z/Y
+    SelectAll,
+    Copy,
+    Cut,
+    Paste,
+    Undo,
+    Redo,
+}
+
+/// drawing order of the commands. Higher layers are drawn on top of lower ones
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum Layer {
+    Background,
+    Normal,
+    /// floating windows, by z-order
+    Window(usize),
+    /// modal dialogs
+    Modal,
+    /// combo lists, menus
+    Popup,
+    Tooltip,
 }
 
 pub trait Renderer {
     fn line(&mut self, p1: Pos, p2: Pos, col: Color);
     fn rectangle(&mut self, rect: &Rect, col: Color);
     fn text(&mut self, pos: Pos, txt: &str, col: Color);
-    fn text_color(&mut self, pos: Pos, txt: &str, align: TextAlign);
     fn frame(&mut self, txt: &str, rect: &Rect, col: Color, coltxt: Color);
     fn checkbox(&mut self, pos: Pos, checked: bool, col: Color);
+    /// draws a radio button glyph. The default implementation uses text
+    fn radio(&mut self, pos: Pos, selected: bool, col: Color) {
+        self.text(pos, if selected { "*" } else { "o" }, col);
+    }
     fn dropdown(&mut self, pos: Pos, checked: bool, col: Color);
     fn progress(&mut self, rect: &Rect, val: f32, back: Color, fore: Color);
 }
@@ -126,42 +293,203 @@ pub const MOUSE_BUTTON_LEFT: usize = 1;
 pub const MOUSE_BUTTON_RIGHT: usize = 2;
 pub const MOUSE_BUTTON_MIDDLE: usize = 4;
 
+pub const KEY_MOD_SHIFT: usize = 1;
+pub const KEY_MOD_CTRL: usize = 2;
+pub const KEY_MOD_ALT: usize = 4;
+
 #[derive(Default)]
 pub struct TextBoxState {
     bkgnd_text: String,
     value: String,
     offset: usize,
     cursor_pos: usize,
+    // first visible line of a textarea
+    line_offset: usize,
+    // other end of the selection. The cursor is one end
+    selection_anchor: Option<usize>,
+    // (value, cursor_pos) snapshots
+    undo_stack: Vec<(String, usize)>,
+    redo_stack: Vec<(String, usize)>,
+    // consecutive typed characters are undone at once
+    typing: bool,
+}
+
+/// a value animated from one state to another
+struct Tween {
+    from: f32,
+    to: f32,
+    // time at the start of the animation
+    start: f64,
+    duration: f32,
+    easing: Easing,
+    // updated during the current frame
+    used: bool,
+}
+
+#[derive(Default)]
+struct ScrollState {
+    offset: Pos,
+    // offset applied to the content during this frame while scrolling smoothly to offset
+    shown: Pos,
+    // content size computed during the previous frame
+    content: Pos,
+    viewport: Rect,
+    command_start: usize,
+}
+
+struct MenuEntry {
+    label: String,
+    shortcut: String,
+    checked: Option<bool>,
+    enabled: bool,
+    // id of the submenu opened by this entry
+    submenu: Id,
+}
+
+/// a menu being built
+struct MenuLevel {
+    id: Id,
+    pos: Pos,
+    open: bool,
+    entries: Vec<MenuEntry>,
+    // the panel is inserted before its submenus in the popup layer
+    command_start: usize,
+    // clicks outside the anchor and the panels close a top-level menu
+    anchor: Rect,
+    areas_start: usize,
+}
+
+#[derive(Default)]
+struct RadioState {
+    selected: usize,
+    // radio buttons of the group, in submission order
+    items: Vec<Id>,
+}
+
+/// selectable rows of a table or a list box
+#[derive(Default)]
+struct RowSelection {
+    // first visible row
+    offset: usize,
+    row_count: usize,
+    selection: HashSet<usize>,
+    // current row for the keyboard
+    cursor: usize,
+    // start of a shift+click range
+    anchor: usize,
+}
+
+#[derive(Default)]
+struct TableState {
+    widths: Vec<Coord>,
+    sort: Option<TableSort>,
+    sort_changed: bool,
+    rows: RowSelection,
+    // column being resized with the mouse
+    resizing: Option<usize>,
+    body: Rect,
+    // the background color of the selected row is pushed while building its cells
+    row_color_pushed: bool,
+}
+
+struct TreeLevel {
+    id: Id,
+    // last child built during this frame
+    last_child: Id,
+}
+
+#[derive(Default)]
+struct ListBoxState {
+    rows: RowSelection,
+    // type-ahead search
+    typed: String,
+    typed_time: f64,
+}
+
+#[derive(Default)]
+struct MessageLogState {
+    // number of lines scrolled up from the last message
+    offset: Coord,
+    // number of lines during the previous frame
+    line_count: Coord,
+}
+
+#[derive(Default)]
+struct TabsState {
+    selected: usize,
+    // labels added during this frame
+    labels: Vec<String>,
+    // tab positions computed during the previous frame
+    rects: Vec<Rect>,
+    strip: Rect,
 }
 
 #[derive(Default)]
 pub struct Context {
     color_manager: ColorManager,
+    // colors of the #[name] markup
+    color_names: HashMap<String, Color>,
     // id generation
     last_id: Id,
     id_prefix: Vec<String>,
-    // timer for animations
-    timer: usize,
+    // time in seconds for the animations. A f32 would lose the frame durations
+    // after a few hours
+    time: f64,
+    // duration of the current frame given by input_time
+    frame_time: Option<f32>,
+    tweens: HashMap<Id, Tween>,
     // user input data
     mouse_pos: (f32, f32),
     mouse_pressed: usize,
     mouse_down: usize,
+    mouse_wheel: Coord,
+    screen_size: (Coord, Coord),
     text_input: String,
     special_keys: Vec<SpecialKey>,
+    key_modifiers: usize,
+    char_keys: Vec<char>,
+    clipboard: ClipboardBox,
     // rendering
-    commands: Vec<Command>,
+    commands: Vec<(Layer, Command)>,
     layouts: Vec<Layout>,
+    // layers being drawn with the clip stack of the layer below
+    layer_stack: Vec<(Layer, Vec<Rect>)>,
+    // mouse input is only accepted inside the current clip area
+    clip_stack: Vec<Rect>,
     // defered widget creation
     next_layout: Option<Layout>,
     next_align: Option<TextAlign>,
+    next_step: Option<f32>,
+    next_show_value: bool,
     // state management
     focus: Id,
     hover: Id,
+    // focusable widgets in submission order
+    focus_chain: Vec<Id>,
     button_state: HashMap<Id, i32>,
     slider_state: HashMap<Id, f32>,
     textbox_state: HashMap<Id, TextBoxState>,
+    scroll_state: HashMap<Id, ScrollState>,
+    tabs_state: HashMap<Id, TabsState>,
+    tabs_stack: Vec<Id>,
+    table_state: HashMap<Id, TableState>,
+    table_stack: Vec<Id>,
+    list_box_state: HashMap<Id, ListBoxState>,
+    message_log_state: HashMap<Id, MessageLogState>,
+    // tree view
+    // tree being built
+    tree_id: Id,
+    tree_stack: Vec<TreeLevel>,
+    // last child of each node during the previous frame
+    tree_last_child: HashMap<Id, Id>,
+    // selected node of each tree
+    tree_selection: HashMap<Id, Id>,
     toggle_group: HashMap<usize, HashSet<Id>>,
     cur_toggle_group: usize,
+    // radio groups
+    radio_state: HashMap<Id, RadioState>,
+    radio_group: Id,
+    radio_changed: bool,
     pressed: bool,
     active: bool,
     // list-buttons
@@ -169,10 +497,55 @@ pub struct Context {
     list_button_width: Coord,
     list_button_label: String,
     list_button_align: TextAlign,
+    // spinner being edited as text
+    spinner_edit: Id,
+    // combo box
+    combo_items: Vec<String>,
+    combo_open: Id,
+    combo_highlight: i32,
+    // menu bar
+    menubar: Rect,
+    menubar_x: Coord,
+    // path of the open menus, top-level menu first
+    menu_open: Vec<Id>,
+    menu_stack: Vec<MenuLevel>,
+    // panel widths computed during the previous frame
+    menu_width: HashMap<Id, Coord>,
+    context_menu_pos: Pos,
+    // tooltip
+    tooltip_id: Id,
+    tooltip_start: f64,
+    tooltip_hovered: bool,
+    tooltip_delay: Option<f32>,
+    // floating windows
+    window_rect: HashMap<Id, Rect>,
+    // position and size requested by the caller
+    window_request: HashMap<Id, Rect>,
+    // back to front order
+    window_order: Vec<Id>,
+    // windows being built
+    window_stack: Vec<Id>,
+    drawn_windows: Vec<Id>,
+    prev_drawn_windows: Vec<Id>,
+    // topmost window under the mouse
+    hover_window: Id,
+    // areas covered by widgets and popups during this/last frame.
+    // They block the mouse for the lower layers
+    layer_areas: Vec<(Layer, Rect)>,
+    prev_layer_areas: Vec<(Layer, Rect)>,
+    // modal dialogs
+    modal_dim: Option<Color>,
+    modal_open: bool,
+    prev_modal_open: bool,
+    // number of modal dialogs being built
+    modal_depth: usize,
     // drag'n drop
     dnd_on: bool,
     dnd_start: (f32, f32),
     dnd_value: f32,
+    dnd_rect: Rect,
+    // handle grabbed in each range slider, true for the high one
+    range_slider_high: HashMap<Id, bool>,
 }
 
 impl Context {
@@ -208,9 +581,38 @@ impl Context {
     pub fn input_mouse_up(&mut self, button: usize) {
         self.mouse_down &= !button;
     }
+    /// size of the screen in cells. Tooltips stay inside it
+    pub fn input_screen_size(&mut self, width: Coord, height: Coord) {
+        self.screen_size = (width, height);
+    }
+    /// time elapsed since the previous frame in seconds.
+    /// Without it, the animations and delays assume 60 frames per second
+    pub fn input_time(&mut self, dt: f32) {
+        self.frame_time = Some(dt);
+    }
+    /// delta is the number of wheel notches. Positive values scroll up
+    pub fn input_mouse_wheel(&mut self, delta: Coord) {
+        self.mouse_wheel += delta;
+    }
     pub fn input_text(&mut self, text: String) {
         self.text_input = text;
     }
+    pub fn input_special_key(&mut self, key: SpecialKey) {
+        self.special_keys.push(key);
+    }
+    /// modifiers is a combination of KEY_MOD_SHIFT, KEY_MOD_CTRL and KEY_MOD_ALT
+    pub fn input_key_modifiers(&mut self, modifiers: usize) {
+        self.key_modifiers = modifiers;
+    }
+    /// a letter or digit key was pressed this frame, whatever the modifiers.
+    /// Used by the menu mnemonics and accelerators
+    pub fn input_char_key(&mut self, c: char) {
+        self.char_keys.push(c.to_ascii_lowercase());
+    }
+    /// replace the default in-memory clipboard, for example with the system one
+    pub fn set_clipboard(&mut self, clipboard: Box<dyn Clipboard>) {
+        self.clipboard = ClipboardBox(clipboard);
+    }
     // =======================================================
     //
     // Core
@@ -219,31 +621,68 @@ impl Context {
     pub fn begin(&mut self) {
         self.layouts.clear();
         self.commands.clear();
+        self.focus_chain.clear();
+        self.clip_stack.clear();
+        self.layer_stack.clear();
         self.layouts.push(Default::default());
+        self.time += f64::from(self.frame_time.take().unwrap_or(DEFAULT_FRAME_TIME));
+        self.update_hover_window();
     }
     pub fn end(&mut self) {
         self.try_commit();
+        // stable sort : the submission order is kept inside a layer
+        self.commands.sort_by_key(|(layer, _)| *layer);
+        self.prev_drawn_windows = std::mem::take(&mut self.drawn_windows);
+        self.prev_layer_areas = std::mem::take(&mut self.layer_areas);
+        self.prev_modal_open = std::mem::take(&mut self.modal_open);
+        if !self.tooltip_hovered {
+            self.tooltip_id = NULL_ID;
+        }
+        self.tooltip_hovered = false;
+        self.prune_tweens();
+        self.update_focus_chain();
+        self.special_keys.clear();
+        self.char_keys.clear();
+        self.text_input.clear();
         self.mouse_pressed = 0;
+        self.mouse_wheel = 0;
         self.last_id = NULL_ID.to_owned();
         self.id_prefix.clear();
-        self.timer += 1;
         //println!("================");
     }
     pub fn render(&mut self, renderer: &mut impl Renderer) {
-        for c in self.commands.iter() {
+        self.visit_visible_commands(|c| match c {
+            Command::Rect(r, col) => renderer.rectangle(r, *col),
+            Command::Text(txt, pos, col) => renderer.text(*pos, txt, *col),
+            Command::Frame(txt, r, col, coltxt) => renderer.frame(txt, r, *col, *coltxt),
+            Command::Line(p1, p2, col) => renderer.line(*p1, *p2, *col),
+            Command::CheckBox(pos, checked, col) => renderer.checkbox(*pos, *checked, *col),
+            Command::Radio(pos, selected, col) => renderer.radio(*pos, *selected, *col),
+            Command::DropDown(pos, checked, col) => renderer.dropdown(*pos, *checked, *col),
+            Command::Progress(r, val, back, fore) => renderer.progress(r, *val, *back, *fore),
+            Command::PushClip(_) | Command::PopClip => unreachable!(),
+        });
+    }
+    /// calls f for every drawing command from the bottom layer to the top one,
+    /// clipped according to the PushClip/PopClip commands.
+    /// The clipping commands themselves are not visited.
+    pub fn visit_visible_commands(&self, mut f: impl FnMut(&Command)) {
+        let mut clips: Vec<Rect> = Vec::new();
+        for (_, c) in self.commands.iter() {
             match c {
-                Command::Rect(r, col) => renderer.rectangle(r, *col),
-                Command::Text(txt, pos, col) => renderer.text(*pos, txt, *col),
-                Command::TextColor(txt, pos, align) => renderer.text_color(*pos, txt, *align),
-                Command::Frame(txt, r, col, coltxt) => renderer.frame(txt, r, *col, *coltxt),
-                Command::Line(p1, p2, col) => renderer.line(*p1, *p2, *col),
-                Command::CheckBox(pos, checked, col) => renderer.checkbox(*pos, *checked, *col),
-                Command::DropDown(pos, checked, col) => renderer.dropdown(*pos, *checked, *col),
-                Command::Progress(r, val, back, fore) => renderer.progress(r, *val, *back, *fore),
+                Command::PushClip(r) => clips.push(*r),
+                Command::PopClip => {
+                    clips.pop();
+                }
+                _ => match clips.last() {
+                    Some(clip) => c.clone().clip(clip).iter().for_each(&mut f),
+                    None => f(c),
+                },
             }
         }
     }
-    pub fn get_render_commands(&mut self) -> &Vec<Command> {
+    /// the commands of the last frame, sorted by layer
+    pub fn get_render_commands(&mut self) -> &Vec<(Layer, Command)> {
         &self.commands
     }
     pub fn pressed(&mut self) -> bool {
@@ -384,7 +823,11 @@ impl Context {
     }
     fn end_container(&mut self) {
         self.try_commit();
-        self.layouts.pop();
+        if let Some(mut layout) = self.layouts.pop() {
+            if layout.defered_iter().any(|c| *c == DeferedCommand::Clip) {
+                self.pop_clip();
+            }
+        }
         self.id_prefix.pop();
     }
     fn next_rectangle(&mut self, width: Coord, height: Coord) -> Rect {
@@ -447,7 +890,19 @@ impl Context {
         let len = text_color_len(label) as Coord;
         let r = self.next_rectangle(len, 1);
         let back = self.get_color(ColorCode::Background);
-        self.defered(DeferedCommand::LabelColor(r, label.to_owned(), back));
+        let fore = self.get_color(ColorCode::Text);
+        self.defered(DeferedCommand::LabelColor(r, label.to_owned(), back, fore));
+        self
+    }
+    /// a multi-line text word wrapped to width characters. The text can contain
+    /// newlines and #[color] markup (see label_color)
+    pub fn paragraph(&mut self, text: &str, width: Coord) -> &mut Self {
+        self.try_commit();
+        let lines = wrap_text(text, width.max(1) as usize);
+        let r = self.next_rectangle(width, lines.len() as Coord);
+        let back = self.get_color(ColorCode::Background);
+        let fore = self.get_color(ColorCode::Text);
+        self.defered(DeferedCommand::Paragraph(r, lines, back, fore));
         self
     }
 
@@ -464,14 +919,26 @@ impl Context {
             DeferedCommand::CheckBox(checked, col) => {
                 self.draw_checkbox(self.last_cursor(), *checked, *col)
             }
+            DeferedCommand::Radio(selected, col) => {
+                self.draw_radio(self.last_cursor(), *selected, *col)
+            }
             DeferedCommand::DropDown(checked, col) => {
                 self.draw_dropdown(self.last_cursor(), *checked, *col)
             }
             DeferedCommand::Label(r, label, col, coltxt) => {
                 self.render_label(*r, label, *col, *coltxt)
             }
-            DeferedCommand::LabelColor(r, label, col) => self.render_label_color(*r, label, *col),
-            _ => (),
+            DeferedCommand::LabelColor(r, label, col, coltxt) => {
+                self.render_label_color(*r, label, *col, *coltxt)
+            }
+            DeferedCommand::Paragraph(r, lines, col, coltxt) => {
+                self.render_paragraph(*r, lines, *col, *coltxt)
+            }
+            DeferedCommand::Scroll(id) => self.start_scroll(*id, r),
+            DeferedCommand::Frame(..) => {
+                self.push_clip(Rect::new(r.x + 1, r.y + 1, r.w - 2, r.h - 2))
+            }
+            DeferedCommand::Clip => self.push_clip(r),
         }
     }
     fn render_label(&mut self, r: Rect, label: &str, col: Color, coltxt: Color) {
@@ -479,10 +946,18 @@ impl Context {
         self.draw_rect(r, col);
         self.draw_text(r, label, align, coltxt);
     }
-    fn render_label_color(&mut self, r: Rect, label: &str, col: Color) {
+    fn render_label_color(&mut self, r: Rect, label: &str, col: Color, coltxt: Color) {
+        let align = self.next_align.take().unwrap_or(TextAlign::Left);
+        self.draw_rect(r, col);
+        self.draw_text_color(r, label, align, coltxt);
+    }
+    fn render_paragraph(&mut self, r: Rect, lines: &[String], col: Color, coltxt: Color) {
         let align = self.next_align.take().unwrap_or(TextAlign::Left);
         self.draw_rect(r, col);
-        self.draw_text_color(r, label, align);
+        for (i, line) in lines.iter().enumerate() {
+            let line_rect = Rect::new(r.x, r.y + i as Coord, r.w, 1);
+            self.draw_text_color(line_rect, line, align, coltxt);
+        }
     }
 
     fn render_button(&mut self, r: Rect, label: &str, col: Color, coltxt: Color) {
@@ -499,28 +974,87 @@ impl Context {
         self.draw_frame(r, &title, col, coltxt);
     }
 
+    // =======================================================
+    //
+    // Tooltips
+    //
+    // =======================================================
+    /// shows a framed text near the mouse when the last widget is hovered long enough.
+    /// The text can contain color markup and several lines
+    pub fn tooltip(&mut self, text: &str) -> &mut Self {
+        if self.hover != self.last_id || self.last_id == NULL_ID {
+            return self;
+        }
+        if self.tooltip_id != self.last_id {
+            self.tooltip_id = self.last_id;
+            self.tooltip_start = self.time;
+        }
+        self.tooltip_hovered = true;
+        let delay = self.tooltip_delay.unwrap_or(TOOLTIP_DELAY);
+        if self.time - self.tooltip_start >= f64::from(delay) {
+            self.draw_tooltip(text);
+        }
+        self
+    }
+    /// time in seconds a widget must be hovered before its tooltip shows
+    pub fn set_tooltip_delay(&mut self, seconds: f32) {
+        self.tooltip_delay = Some(seconds);
+    }
+    fn draw_tooltip(&mut self, text: &str) {
+        let lines: Vec<&str> = text.lines().collect();
+        let width = lines
+            .iter()
+            .map(|l| text_color_len(l) as Coord)
+            .max()
+            .unwrap_or(0)
+            + 2;
+        let height = lines.len() as Coord + 2;
+        let mouse_pos: Pos = self.mouse_pos.into();
+        let (screen_width, screen_height) = self.screen_size;
+        let mut x = mouse_pos.x + 1;
+        let mut y = mouse_pos.y + 1;
+        // flip to the other side of the mouse if the tooltip goes out of the screen
+        if screen_width > 0 && x + width > screen_width {
+            x = (mouse_pos.x - width).max(0);
+        }
+        if screen_height > 0 && y + height > screen_height {
+            y = (mouse_pos.y - height).max(0);
+        }
+        let r = Rect::new(x, y, width, height);
+        let back = self.get_color(ColorCode::Background);
+        let fore = self.get_color(ColorCode::Text);
+        self.push_layer(Layer::Tooltip);
+        self.draw_frame(r, "", back, fore);
+        for (i, line) in lines.iter().enumerate() {
+            let line_rect = Rect::new(x + 1, y + 1 + i as Coord, width - 2, 1);
+            self.draw_text_color(line_rect, line, TextAlign::Left, fore);
+        }
+        self.pop_layer();
+    }
+
     // =======================================================
     //
     // Basic drawing functions
     //
     // =======================================================
     fn draw_progress(&mut self, r: Rect, coef: f32, back: Color, fore: Color) {
-        self.commands.push(Command::Progress(r, coef, back, fore));
+        self.push_command(Command::Progress(r, coef, back, fore));
     }
     fn draw_checkbox(&mut self, p: Pos, checked: bool, col: Color) {
-        self.commands.push(Command::CheckBox(p, checked, col));
+        self.push_command(Command::CheckBox(p, checked, col));
+    }
+    fn draw_radio(&mut self, p: Pos, selected: bool, col: Color) {
+        self.push_command(Command::Radio(p, selected, col));
     }
     fn draw_dropdown(&mut self, p: Pos, checked: bool, col: Color) {
-        self.commands
-            .push(Command::DropDown(Pos { x: p.x + 1, y: p.y }, checked, col));
+        self.push_command(Command::DropDown(Pos { x: p.x + 1, y: p.y }, checked, col));
     }
     fn draw_frame(&mut self, r: Rect, title: &str, col: Color, coltxt: Color) {
-        self.commands
-            .push(Command::Frame(title.to_owned(), r, col, coltxt));
+        self.push_command(Command::Frame(title.to_owned(), r, col, coltxt));
     }
 
     fn draw_line(&mut self, x1: Coord, y1: Coord, x2: Coord, y2: Coord, col: Color) {
-        self.commands.push(Command::Line(
+        self.push_command(Command::Line(
             Pos { x: x1, y: y1 },
             Pos { x: x2, y: y2 },
             col,
@@ -528,21 +1062,35 @@ impl Context {
     }
 
     fn draw_rect(&mut self, r: Rect, col: Color) {
-        self.commands.push(Command::Rect(r, col));
+        self.push_command(Command::Rect(r, col));
     }
 
     fn draw_text(&mut self, r: Rect, txt: &str, align: TextAlign, col: Color) {
         let (pos, truncated_text) = format_text(r, txt, align);
-        self.commands.push(Command::Text(truncated_text, pos, col));
+        self.push_command(Command::Text(truncated_text, pos, col));
     }
 
-    fn draw_text_color(&mut self, r: Rect, txt: &str, align: TextAlign) {
-        self.commands
-            .push(Command::TextColor(txt.to_owned(), r.into(), align));
+    fn update_control(&mut self, id: Id, r: &Rect, hold_focus: bool) {
+        if !self.input_blocked() {
+            self.focus_chain.push(id);
+        } else if self.focus == id {
+            self.focus = NULL_ID;
+        }
+        self.update_mouse_control(id, r, hold_focus);
     }
 
-    fn update_control(&mut self, id: Id, r: &Rect, hold_focus: bool) {
-        let mouse_over = r.contains(self.mouse_pos.into());
+    /// same as update_control for widgets that can't get the focus from the keyboard
+    fn update_mouse_control(&mut self, id: Id, r: &Rect, hold_focus: bool) {
+        let mouse_pos = self.mouse_pos.into();
+        let visible = match self.clip_stack.last() {
+            Some(clip) => r.intersection(clip),
+            None => *r,
+        };
+        if !visible.is_empty() {
+            self.layer_areas.push((self.current_layer(), visible));
+        }
+        let mouse_over =
+            r.contains(mouse_pos) && self.clip_contains(mouse_pos) && !self.is_covered(mouse_pos);
         let pressed = self.mouse_pressed != 0;
         if mouse_over {
             self.hover = id;
@@ -551,25 +1099,201 @@ impl Context {
             }
         } else {
             self.hover = NULL_ID.to_owned();
-            if self.focus == id
-                && ((!hold_focus && pressed) || (hold_focus && self.mouse_down == 0))
+            if self.focus == id && (pressed || (hold_focus && self.dnd_on && self.mouse_down == 0))
             {
                 self.set_focus(NULL_ID.to_owned());
             }
         }
     }
 
+    /// returns true if the widget has the focus and Enter or Space was pressed.
+    /// The key is consumed.
+    fn key_activated(&mut self, id: Id) -> bool {
+        if self.focus != id {
+            return false;
+        }
+        if let Some(i) = self
+            .special_keys
+            .iter()
+            .position(|k| *k == SpecialKey::Enter || *k == SpecialKey::Space)
+        {
+            self.special_keys.remove(i);
+            return true;
+        }
+        false
+    }
+    /// consumes a special key pressed this frame
+    fn take_special_key(&mut self, key: SpecialKey) -> bool {
+        if let Some(i) = self.special_keys.iter().position(|k| *k == key) {
+            self.special_keys.remove(i);
+            return true;
+        }
+        false
+    }
+
+    /// moves the focus along the focus chain using the keys not consumed by the widgets
+    /// Tab/Down/Right : next widget, Shift-Tab/Up/Left : previous widget, Escape : no focus
+    fn update_focus_chain(&mut self) {
+        if self.focus_chain.is_empty() {
+            return;
+        }
+        let len = self.focus_chain.len();
+        let mut index = self.focus_chain.iter().position(|id| *id == self.focus);
+        let mut moved = false;
+        for key in self.special_keys.iter() {
+            let forward = match key {
+                SpecialKey::Tab => self.key_modifiers & KEY_MOD_SHIFT == 0,
+                SpecialKey::Down | SpecialKey::Right => true,
+                SpecialKey::Up | SpecialKey::Left => false,
+                SpecialKey::Escape => {
+                    index = None;
+                    moved = true;
+                    continue;
+                }
+                _ => continue,
+            };
+            moved = true;
+            index = Some(match index {
+                None if forward => 0,
+                None => len - 1,
+                Some(i) if forward => (i + 1) % len,
+                Some(i) => (i + len - 1) % len,
+            });
+        }
+        if moved {
+            let focus = index.map_or(NULL_ID, |i| self.focus_chain[i]);
+            self.set_focus(focus);
+        }
+    }
+
     fn start_dnd(&mut self, value: f32) {
         self.dnd_on = true;
         self.dnd_value = value;
         self.dnd_start = self.mouse_pos;
     }
 
+    fn push_clip(&mut self, r: Rect) {
+        let r = match self.clip_stack.last() {
+            Some(clip) => r.intersection(clip),
+            None => r,
+        };
+        self.clip_stack.push(r);
+        self.push_command(Command::PushClip(r));
+    }
+
+    fn pop_clip(&mut self) {
+        self.clip_stack.pop();
+        self.push_command(Command::PopClip);
+    }
+
+    /// the following widgets are drawn in this layer until end_layer.
+    /// The clipping area of the current layer doesn't apply to the new one
+    pub fn begin_layer(&mut self, layer: Layer) {
+        self.try_commit();
+        self.push_layer(layer);
+    }
+
+    pub fn end_layer(&mut self) {
+        self.try_commit();
+        self.pop_layer();
+    }
+
+    fn push_layer(&mut self, layer: Layer) {
+        let clip_stack = std::mem::take(&mut self.clip_stack);
+        self.layer_stack.push((layer, clip_stack));
+    }
+
+    fn pop_layer(&mut self) {
+        let (_, clip_stack) = self
+            .layer_stack
+            .pop()
+            .expect("unmatched begin/end layer calls");
+        self.clip_stack = clip_stack;
+    }
+
+    fn current_layer(&self) -> Layer {
+        self.layer_stack
+            .last()
+            .map_or(Layer::Normal, |(layer, _)| *layer)
+    }
+
+    fn push_command(&mut self, c: Command) {
+        let layer = self.current_layer();
+        self.commands.push((layer, c));
+    }
+
+    /// is this position covered by a widget or a popup on a layer above the current one
+    /// during the previous frame. Only the topmost widget under the mouse gets hovered.
+    fn is_covered(&self, pos: Pos) -> bool {
+        let layer = self.current_layer();
+        let window_covered = self.hover_window != NULL_ID
+            && self.hover_window != self.current_window()
+            && self.window_layer(self.hover_window) > layer;
+        window_covered
+            || self.input_blocked()
+            || self
+                .prev_layer_areas
+                .iter()
+                .any(|(area_layer, r)| *area_layer > layer && r.contains(pos))
+    }
+
+    /// a modal dialog is open and we're not inside it
+    fn input_blocked(&self) -> bool {
+        (self.modal_open || self.prev_modal_open) && self.modal_depth == 0
+    }
+
+    fn window_layer(&self, window_id: Id) -> Layer {
+        Layer::Window(
+            self.window_order
+                .iter()
+                .position(|id| *id == window_id)
+                .unwrap_or(0),
+        )
+    }
+
+    fn clip_contains(&self, p: Pos) -> bool {
+        match self.clip_stack.last() {
+            Some(clip) => clip.contains(p),
+            None => true,
+        }
+    }
+
+    fn current_window(&self) -> Id {
+        self.window_stack.last().copied().unwrap_or(NULL_ID)
+    }
+
+    /// finds the topmost window under the mouse among those drawn during the last frame.
+    /// It is brought to front when clicked.
+    fn update_hover_window(&mut self) {
+        let mouse_pos = self.mouse_pos.into();
+        self.hover_window = NULL_ID;
+        for id in self.window_order.iter().rev() {
+            if self.prev_drawn_windows.contains(id)
+                && matches!(self.window_rect.get(id), Some(r) if r.contains(mouse_pos))
+            {
+                self.hover_window = *id;
+                break;
+            }
+        }
+        if self.mouse_pressed != 0 && self.hover_window != NULL_ID {
+            let hover_window = self.hover_window;
+            self.window_order.retain(|id| *id != hover_window);
+            self.window_order.push(hover_window);
+        }
+    }
+
     fn set_focus(&mut self, id: Id) {
         self.focus = id;
     }
 }
 
+/// an id for a secondary state of a widget, like its animations
+fn derived_id(id: Id, name: &str) -> Id {
+    let mut hasher = DefaultHasher::new();
+    (id, name).hash(&mut hasher);
+    hasher.finish()
+}
+
 fn format_text(r: Rect, txt: &str, align: TextAlign) -> (Pos, String) {
     let mut p: Pos = r.into();
     let truncated_txt: String;
@@ -649,14 +1373,6 @@ mod tests {
                 x += 1;
             }
         }
-        fn text_color(&mut self, pos: ui::Pos, txt: &str, _align: ui::TextAlign) {
-            let mut x = pos.x as usize;
-            let y = pos.y as usize;
-            for c in txt.chars() {
-                self.character[x][y] = c;
-                x += 1;
-            }
-        }
         fn progress(&mut self, _r: &ui::Rect, _value: f32, _back: ui::Color, _fore: ui::Color) {}
         fn frame(&mut self, txt: &str, rect: &ui::Rect, _col: ui::Color, coltxt: ui::Color) {
             let rx = rect.x as usize;
@@ -686,6 +1402,12 @@ mod tests {
         ctx.end();
         ctx.render(&mut rend);
         assert!(rend.assert("test", 0, 0));
+        // a left click is not lost when another button is pressed in the same frame
+        ctx.input_mouse_pos(1.0, 0.0);
+        ctx.input_mouse_down(ui::MOUSE_BUTTON_LEFT | ui::MOUSE_BUTTON_RIGHT);
+        ctx.begin();
+        assert!(ctx.button("0", "test").pressed());
+        ctx.end();
     }
     #[test]
     fn test_vbox() {
@@ -742,4 +1464,1057 @@ mod tests {
         assert!(rend.assert("1", 0, 0));
         assert!(rend.assert("2", 0, 2));
     }
+    #[test]
+    fn test_focus_chain() {
+        let mut ctx = ui::Context::new();
+        let build = |ctx: &mut ui::Context| {
+            ctx.begin();
+            let b1 = ctx.button("b1", "one").pressed();
+            let b2 = ctx.button("b2", "two").pressed();
+            ctx.end();
+            (b1, b2)
+        };
+        build(&mut ctx);
+        ctx.input_special_key(ui::SpecialKey::Tab);
+        build(&mut ctx);
+        ctx.input_special_key(ui::SpecialKey::Tab);
+        build(&mut ctx);
+        ctx.input_special_key(ui::SpecialKey::Enter);
+        assert_eq!(build(&mut ctx), (false, true));
+        ctx.input_key_modifiers(ui::KEY_MOD_SHIFT);
+        ctx.input_special_key(ui::SpecialKey::Tab);
+        build(&mut ctx);
+        ctx.input_special_key(ui::SpecialKey::Space);
+        assert_eq!(build(&mut ctx), (true, false));
+    }
+    #[test]
+    fn test_scroll() {
+        let mut ctx = ui::Context::new();
+        let build = |ctx: &mut ui::Context| {
+            ctx.begin();
+            ctx.scroll_begin("scroll", 5, 3);
+            for i in 1..=5 {
+                ctx.label(&format!("{}", i));
+            }
+            ctx.scroll_end();
+            ctx.end();
+        };
+        build(&mut ctx);
+        ctx.input_mouse_pos(1.0, 1.0);
+        ctx.input_mouse_wheel(-1);
+        build(&mut ctx);
+        // smooth scrolling
+        ctx.input_time(1.0);
+        build(&mut ctx);
+        let mut rend = AsciiRenderer::new();
+        ctx.render(&mut rend);
+        assert!(rend.assert("3", 0, 0));
+        assert!(rend.assert("5", 0, 2));
+        assert!(rend.assert(" ", 0, 3));
+    }
+    #[test]
+    fn test_frame_clipping() {
+        let mut rend = AsciiRenderer::new();
+        let mut ctx = ui::Context::new();
+        ctx.begin();
+        ctx.frame_begin("frame", "", 6, 3);
+        ctx.hbox_begin("hbox");
+        ctx.label("abc");
+        ctx.label("def");
+        ctx.hbox_end();
+        ctx.frame_end();
+        ctx.end();
+        ctx.render(&mut rend);
+        assert!(rend.assert("abcd", 1, 1));
+        assert!(rend.assert(" ", 5, 1));
+        assert!(rend.assert(" ", 6, 1));
+    }
+    #[test]
+    fn test_partly_clipped_frame() {
+        let mut ctx = ui::Context::new();
+        let build = |ctx: &mut ui::Context| {
+            ctx.begin();
+            ctx.scroll_begin("scroll", 8, 2);
+            ctx.label("top");
+            ctx.frame_begin("frame", "ab", 6, 3);
+            ctx.hbox_begin("hbox");
+            ctx.label("x");
+            ctx.separator();
+            ctx.label("y");
+            ctx.hbox_end();
+            ctx.frame_end();
+            ctx.scroll_end();
+            ctx.end();
+        };
+        build(&mut ctx);
+        ctx.input_mouse_pos(1.0, 1.0);
+        ctx.input_mouse_wheel(-2);
+        build(&mut ctx);
+        ctx.input_time(1.0);
+        build(&mut ctx);
+        let mut rend = AsciiRenderer::new();
+        ctx.render(&mut rend);
+        // the frame title row is scrolled out, its borders are still drawn
+        assert!(rend.assert("└     ┘", 0, 1));
+        assert!(rend.assert("  ", 0, 2));
+        let mut vertical_lines = 0;
+        ctx.visit_visible_commands(|c| {
+            if let ui::Command::Line(p1, p2, _) = c {
+                if p1.x == p2.x && p1.y == 0 {
+                    vertical_lines += 1;
+                }
+            }
+        });
+        // the frame sides and the separator
+        assert_eq!(vertical_lines, 3);
+    }
+    #[test]
+    fn test_combo() {
+        let mut ctx = ui::Context::new();
+        let build = |ctx: &mut ui::Context| {
+            ctx.begin();
+            ctx.combo_begin("combo", 0);
+            let mut selected = None;
+            for (i, label) in ["aaa", "bbb", "ccc"].iter().enumerate() {
+                if ctx.combo_item(label) {
+                    selected = Some(i);
+                }
+            }
+            let changed = ctx.combo_end();
+            ctx.label("zzzzz");
+            ctx.end();
+            (selected, changed)
+        };
+        build(&mut ctx);
+        ctx.input_mouse_pos(0.0, 0.0);
+        ctx.input_mouse_down(ui::MOUSE_BUTTON_LEFT);
+        ctx.input_mouse_up(ui::MOUSE_BUTTON_LEFT);
+        assert_eq!(build(&mut ctx), (Some(0), false));
+        let mut rend = AsciiRenderer::new();
+        ctx.render(&mut rend);
+        assert!(rend.assert("aaa", 0, 1));
+        assert!(rend.assert("ccc", 0, 3));
+        ctx.input_mouse_pos(1.0, 3.0);
+        ctx.input_mouse_down(ui::MOUSE_BUTTON_LEFT);
+        assert_eq!(build(&mut ctx), (Some(0), true));
+        assert_eq!(build(&mut ctx), (Some(2), false));
+        let mut rend = AsciiRenderer::new();
+        ctx.render(&mut rend);
+        assert!(rend.assert("zzzzz", 0, 1));
+    }
+    #[test]
+    fn test_window_drag() {
+        let mut ctx = ui::Context::new();
+        let build = |ctx: &mut ui::Context, x: ui::Coord| {
+            ctx.begin();
+            ctx.frame_window_begin("win", "", x, 0, 10, 5);
+            ctx.frame_window_end();
+            ctx.end();
+        };
+        build(&mut ctx, 0);
+        ctx.input_mouse_pos(3.0, 0.0);
+        ctx.input_mouse_down(ui::MOUSE_BUTTON_LEFT);
+        build(&mut ctx, 0);
+        ctx.input_mouse_pos(5.0, 2.0);
+        build(&mut ctx, 0);
+        ctx.input_mouse_up(ui::MOUSE_BUTTON_LEFT);
+        build(&mut ctx, 0);
+        let mut rend = AsciiRenderer::new();
+        ctx.render(&mut rend);
+        assert!(rend.assert("1", 2, 2));
+        assert!(rend.assert("4", 11, 6));
+        // the caller moves the window
+        build(&mut ctx, 20);
+        let mut rend = AsciiRenderer::new();
+        ctx.render(&mut rend);
+        assert!(rend.assert("1", 20, 0));
+    }
+    #[test]
+    fn test_window_order() {
+        let mut ctx = ui::Context::new();
+        let build = |ctx: &mut ui::Context| {
+            ctx.begin();
+            ctx.frame_window_begin("a", "", 0, 0, 10, 5);
+            ctx.label("");
+            let pressed = ctx.button("button", "bbbbbbbb").pressed();
+            ctx.frame_window_end();
+            ctx.frame_window_begin("b", "", 5, 2, 10, 5);
+            ctx.frame_window_end();
+            ctx.end();
+            pressed
+        };
+        build(&mut ctx);
+        let mut rend = AsciiRenderer::new();
+        ctx.render(&mut rend);
+        assert!(rend.assert("1", 5, 2));
+        // b is on top : a's button does not get the click
+        ctx.input_mouse_pos(6.0, 2.0);
+        ctx.input_mouse_down(ui::MOUSE_BUTTON_LEFT);
+        ctx.input_mouse_up(ui::MOUSE_BUTTON_LEFT);
+        assert!(!build(&mut ctx));
+        // clicking a brings it to front
+        ctx.input_mouse_pos(2.0, 2.0);
+        ctx.input_mouse_down(ui::MOUSE_BUTTON_LEFT);
+        ctx.input_mouse_up(ui::MOUSE_BUTTON_LEFT);
+        assert!(build(&mut ctx));
+        let mut rend = AsciiRenderer::new();
+        ctx.render(&mut rend);
+        assert!(rend.assert("bbbb", 5, 2));
+    }
+    #[test]
+    fn test_textarea() {
+        let mut ctx = ui::Context::new();
+        let build = |ctx: &mut ui::Context| {
+            ctx.begin();
+            ctx.textarea("area", 10, 3, None, None);
+            let id = ctx.last_id();
+            ctx.end();
+            id
+        };
+        build(&mut ctx);
+        ctx.input_mouse_pos(1.0, 1.0);
+        ctx.input_mouse_down(ui::MOUSE_BUTTON_LEFT);
+        ctx.input_mouse_up(ui::MOUSE_BUTTON_LEFT);
+        build(&mut ctx);
+        ctx.input_text("ab".to_owned());
+        build(&mut ctx);
+        ctx.input_special_key(ui::SpecialKey::Enter);
+        ctx.input_text("cd".to_owned());
+        build(&mut ctx);
+        ctx.input_special_key(ui::SpecialKey::Up);
+        build(&mut ctx);
+        ctx.input_text("X".to_owned());
+        let id = build(&mut ctx);
+        assert_eq!(ctx.text(id), "abX\ncd");
+        let mut rend = AsciiRenderer::new();
+        ctx.render(&mut rend);
+        assert!(rend.assert("cd", 0, 1));
+    }
+    #[test]
+    fn test_textbox_edit() {
+        let mut ctx = ui::Context::new();
+        let build = |ctx: &mut ui::Context| {
+            ctx.begin();
+            ctx.textbox("box", 20, None, None);
+            let id = ctx.last_id();
+            ctx.end();
+            id
+        };
+        build(&mut ctx);
+        ctx.input_mouse_pos(1.0, 0.0);
+        ctx.input_mouse_down(ui::MOUSE_BUTTON_LEFT);
+        ctx.input_mouse_up(ui::MOUSE_BUTTON_LEFT);
+        build(&mut ctx);
+        ctx.input_text("hello world".to_owned());
+        build(&mut ctx);
+        // select the last word and move it to the start
+        ctx.input_key_modifiers(ui::KEY_MOD_SHIFT | ui::KEY_MOD_CTRL);
+        ctx.input_special_key(ui::SpecialKey::Left);
+        build(&mut ctx);
+        ctx.input_key_modifiers(0);
+        ctx.input_special_key(ui::SpecialKey::Cut);
+        ctx.input_special_key(ui::SpecialKey::Home);
+        ctx.input_special_key(ui::SpecialKey::Paste);
+        let id = build(&mut ctx);
+        assert_eq!(ctx.text(id), "worldhello ");
+        ctx.input_special_key(ui::SpecialKey::Undo);
+        ctx.input_special_key(ui::SpecialKey::Undo);
+        build(&mut ctx);
+        assert_eq!(ctx.text(id), "hello world");
+        ctx.input_special_key(ui::SpecialKey::Redo);
+        build(&mut ctx);
+        assert_eq!(ctx.text(id), "hello ");
+        // typing replaces the selection
+        ctx.input_special_key(ui::SpecialKey::SelectAll);
+        build(&mut ctx);
+        let mut rend = AsciiRenderer::new();
+        ctx.render(&mut rend);
+        assert!(rend.assert("hello", 0, 0));
+        ctx.input_text("bye".to_owned());
+        build(&mut ctx);
+        assert_eq!(ctx.text(id), "bye");
+    }
+    #[test]
+    fn test_tabs() {
+        let mut ctx = ui::Context::new();
+        let build = |ctx: &mut ui::Context| {
+            ctx.begin();
+            ctx.tabs_begin("tabs", 12, 4);
+            if ctx.tab("one") {
+                ctx.label("first");
+            }
+            if ctx.tab("two") {
+                ctx.label("second");
+            }
+            let selected = ctx.tabs_end();
+            ctx.end();
+            selected
+        };
+        assert_eq!(build(&mut ctx), 0);
+        let mut rend = AsciiRenderer::new();
+        ctx.render(&mut rend);
+        assert!(rend.assert(" one  two ", 1, 0));
+        assert!(rend.assert("first", 1, 2));
+        // click on the second tab
+        ctx.input_mouse_pos(7.0, 0.0);
+        ctx.input_mouse_down(ui::MOUSE_BUTTON_LEFT);
+        ctx.input_mouse_up(ui::MOUSE_BUTTON_LEFT);
+        assert_eq!(build(&mut ctx), 1);
+        let mut rend = AsciiRenderer::new();
+        ctx.render(&mut rend);
+        assert!(rend.assert("second", 1, 2));
+        // the strip has the focus. Right wraps around to the first tab
+        ctx.input_special_key(ui::SpecialKey::Right);
+        assert_eq!(build(&mut ctx), 0);
+    }
+    #[test]
+    fn test_menu() {
+        let mut ctx = ui::Context::new();
+        let build = |ctx: &mut ui::Context| {
+            let mut activated = Vec::new();
+            ctx.begin();
+            ctx.menubar_begin("menubar", 30);
+            ctx.menu_begin("&File");
+            if ctx.menu_item("&Open", Some("Ctrl+O")) {
+                activated.push("open");
+            }
+            ctx.menu_begin("&Recent");
+            if ctx.menu_item("a.txt", None) {
+                activated.push("a.txt");
+            }
+            ctx.menu_end();
+            ctx.menu_end();
+            ctx.menu_begin("&Edit");
+            if ctx.menu_check("&Wrap", None, true) {
+                activated.push("wrap");
+            }
+            ctx.menu_entry("&Paste", None, None, false);
+            ctx.menu_end();
+            ctx.menubar_end();
+            ctx.end();
+            activated
+        };
+        build(&mut ctx);
+        // accelerators work with closed menus
+        ctx.input_key_modifiers(ui::KEY_MOD_CTRL);
+        ctx.input_char_key('o');
+        assert_eq!(build(&mut ctx), vec!["open"]);
+        ctx.input_key_modifiers(0);
+        // open the File menu and hover the Recent submenu
+        ctx.input_mouse_pos(1.0, 0.0);
+        ctx.input_mouse_down(ui::MOUSE_BUTTON_LEFT);
+        ctx.input_mouse_up(ui::MOUSE_BUTTON_LEFT);
+        build(&mut ctx);
+        ctx.input_mouse_pos(3.0, 2.0);
+        build(&mut ctx);
+        build(&mut ctx);
+        let mut rend = AsciiRenderer::new();
+        ctx.render(&mut rend);
+        assert!(rend.assert("Open", 2, 1));
+        assert!(rend.assert("Ctrl+O", 10, 1));
+        assert!(rend.assert("a.txt", 20, 2));
+        ctx.input_mouse_pos(20.0, 2.0);
+        ctx.input_mouse_down(ui::MOUSE_BUTTON_LEFT);
+        ctx.input_mouse_up(ui::MOUSE_BUTTON_LEFT);
+        assert_eq!(build(&mut ctx), vec!["a.txt"]);
+        let mut rend = AsciiRenderer::new();
+        build(&mut ctx);
+        ctx.render(&mut rend);
+        assert!(!rend.assert("Open", 2, 1));
+        // Alt+E opens the Edit menu, W activates its first entry, P is disabled
+        ctx.input_key_modifiers(ui::KEY_MOD_ALT);
+        ctx.input_char_key('e');
+        build(&mut ctx);
+        ctx.input_key_modifiers(0);
+        ctx.input_char_key('p');
+        assert!(build(&mut ctx).is_empty());
+        ctx.input_char_key('w');
+        assert_eq!(build(&mut ctx), vec!["wrap"]);
+    }
+    #[test]
+    fn test_context_menu() {
+        let mut ctx = ui::Context::new();
+        let build = |ctx: &mut ui::Context| {
+            let mut activated = Vec::new();
+            ctx.begin();
+            ctx.button("sword", "sword");
+            ctx.context_menu_begin();
+            if ctx.menu_item("Use", None) {
+                activated.push("use");
+            }
+            if ctx.menu_item("Drop", None) {
+                activated.push("drop");
+            }
+            ctx.context_menu_end();
+            ctx.end();
+            activated
+        };
+        let right_click = |ctx: &mut ui::Context, x: f32, y: f32| {
+            ctx.input_mouse_pos(x, y);
+            ctx.input_mouse_down(ui::MOUSE_BUTTON_RIGHT);
+            ctx.input_mouse_up(ui::MOUSE_BUTTON_RIGHT);
+        };
+        build(&mut ctx);
+        right_click(&mut ctx, 2.0, 0.0);
+        build(&mut ctx);
+        build(&mut ctx);
+        let mut rend = AsciiRenderer::new();
+        ctx.render(&mut rend);
+        assert!(rend.assert("Use", 4, 0));
+        assert!(rend.assert("Drop", 4, 1));
+        ctx.input_mouse_pos(4.0, 1.0);
+        ctx.input_mouse_down(ui::MOUSE_BUTTON_LEFT);
+        ctx.input_mouse_up(ui::MOUSE_BUTTON_LEFT);
+        assert_eq!(build(&mut ctx), vec!["drop"]);
+        // Escape and outside clicks close the menu
+        right_click(&mut ctx, 2.0, 0.0);
+        build(&mut ctx);
+        ctx.input_special_key(ui::SpecialKey::Escape);
+        build(&mut ctx);
+        let mut rend = AsciiRenderer::new();
+        build(&mut ctx);
+        ctx.render(&mut rend);
+        assert!(!rend.assert("Use", 4, 0));
+        right_click(&mut ctx, 2.0, 0.0);
+        build(&mut ctx);
+        ctx.input_mouse_pos(15.0, 5.0);
+        ctx.input_mouse_down(ui::MOUSE_BUTTON_LEFT);
+        ctx.input_mouse_up(ui::MOUSE_BUTTON_LEFT);
+        build(&mut ctx);
+        let mut rend = AsciiRenderer::new();
+        build(&mut ctx);
+        ctx.render(&mut rend);
+        assert!(!rend.assert("Use", 4, 0));
+    }
+    #[test]
+    fn test_tooltip() {
+        let mut ctx = ui::Context::new();
+        ctx.set_tooltip_delay(0.03);
+        ctx.input_screen_size(20, 10);
+        let build = |ctx: &mut ui::Context| {
+            ctx.begin();
+            ctx.button("ok", "ok").tooltip("help");
+            ctx.end();
+            let mut rend = AsciiRenderer::new();
+            ctx.render(&mut rend);
+            rend
+        };
+        ctx.input_mouse_pos(1.0, 0.0);
+        build(&mut ctx);
+        assert!(!build(&mut ctx).assert("help", 3, 2));
+        assert!(build(&mut ctx).assert("help", 3, 2));
+        // leaving the widget resets the delay
+        ctx.input_mouse_pos(10.0, 5.0);
+        build(&mut ctx);
+        ctx.input_mouse_pos(1.0, 0.0);
+        assert!(!build(&mut ctx).assert("help", 3, 2));
+        build(&mut ctx);
+        // near the right border, the tooltip is on the left side of the mouse
+        ctx.input_screen_size(4, 10);
+        assert!(build(&mut ctx).assert("help", 1, 2));
+    }
+    #[test]
+    fn test_layers() {
+        let mut ctx = ui::Context::new();
+        let build = |ctx: &mut ui::Context| {
+            ctx.begin();
+            ctx.begin_layer(ui::Layer::Popup);
+            let top = ctx.button("top", "top").pressed();
+            ctx.end_layer();
+            ctx.move_cursor(0, -1);
+            let below = ctx.button("below", "xxxxx").pressed();
+            ctx.end();
+            (top, below)
+        };
+        build(&mut ctx);
+        let mut rend = AsciiRenderer::new();
+        ctx.render(&mut rend);
+        // the popup layer is drawn above widgets submitted later
+        assert!(rend.assert("topxx", 0, 0));
+        // only the topmost widget gets the click
+        ctx.input_mouse_pos(1.0, 0.0);
+        ctx.input_mouse_down(ui::MOUSE_BUTTON_LEFT);
+        ctx.input_mouse_up(ui::MOUSE_BUTTON_LEFT);
+        assert_eq!(build(&mut ctx), (true, false));
+        ctx.input_mouse_pos(4.0, 0.0);
+        ctx.input_mouse_down(ui::MOUSE_BUTTON_LEFT);
+        ctx.input_mouse_up(ui::MOUSE_BUTTON_LEFT);
+        assert_eq!(build(&mut ctx), (false, true));
+    }
+    #[test]
+    fn test_modal() {
+        let mut ctx = ui::Context::new();
+        ctx.input_screen_size(30, 10);
+        let build = |ctx: &mut ui::Context, open: bool| {
+            ctx.begin();
+            let behind = ctx.button("quit", "Quit").pressed();
+            let result = if open {
+                ctx.confirm("confirm", "Quit", "Sure?")
+            } else {
+                None
+            };
+            ctx.end();
+            (behind, result)
+        };
+        build(&mut ctx, true);
+        let mut rend = AsciiRenderer::new();
+        ctx.render(&mut rend);
+        assert!(rend.assert("Sure?", 10, 3));
+        assert!(rend.assert(" Yes   No ", 10, 5));
+        // the widgets behind the dialog are disabled
+        ctx.input_mouse_pos(1.0, 0.0);
+        ctx.input_mouse_down(ui::MOUSE_BUTTON_LEFT);
+        ctx.input_mouse_up(ui::MOUSE_BUTTON_LEFT);
+        assert_eq!(build(&mut ctx, true), (false, None));
+        ctx.input_mouse_pos(17.0, 5.0);
+        ctx.input_mouse_down(ui::MOUSE_BUTTON_LEFT);
+        ctx.input_mouse_up(ui::MOUSE_BUTTON_LEFT);
+        assert_eq!(build(&mut ctx, true), (false, Some(ui::DialogResult::No)));
+        // Escape answers No. Enter activates the focused button
+        ctx.input_special_key(ui::SpecialKey::Escape);
+        assert_eq!(build(&mut ctx, true), (false, Some(ui::DialogResult::No)));
+        build(&mut ctx, true);
+        ctx.input_special_key(ui::SpecialKey::Enter);
+        assert_eq!(build(&mut ctx, true), (false, Some(ui::DialogResult::Yes)));
+        // once closed, the widgets behind get the input back
+        ctx.input_mouse_pos(1.0, 0.0);
+        ctx.input_mouse_down(ui::MOUSE_BUTTON_LEFT);
+        ctx.input_mouse_up(ui::MOUSE_BUTTON_LEFT);
+        assert_eq!(build(&mut ctx, false), (true, None));
+        // Escape on a message box answers Cancel
+        ctx.begin();
+        ctx.message_box("message", "Info", "Done");
+        ctx.end();
+        ctx.input_special_key(ui::SpecialKey::Escape);
+        ctx.begin();
+        let result = ctx.message_box("message", "Info", "Done");
+        ctx.end();
+        assert_eq!(result, Some(ui::DialogResult::Cancel));
+    }
+    #[test]
+    fn test_radio() {
+        let mut ctx = ui::Context::new();
+        let build = |ctx: &mut ui::Context| {
+            ctx.begin();
+            ctx.radio_begin("difficulty", 1);
+            ctx.radio("easy");
+            ctx.radio("normal");
+            ctx.radio("hard");
+            let changed = ctx.radio_end();
+            ctx.end();
+            changed
+        };
+        assert_eq!(build(&mut ctx), None);
+        let mut rend = AsciiRenderer::new();
+        ctx.render(&mut rend);
+        assert!(rend.assert("o easy", 0, 0));
+        assert!(rend.assert("* normal", 0, 1));
+        ctx.input_mouse_pos(3.0, 2.0);
+        ctx.input_mouse_down(ui::MOUSE_BUTTON_LEFT);
+        ctx.input_mouse_up(ui::MOUSE_BUTTON_LEFT);
+        assert_eq!(build(&mut ctx), Some(2));
+        assert_eq!(build(&mut ctx), None);
+        // the arrow keys move the selection inside the group
+        ctx.input_special_key(ui::SpecialKey::Down);
+        assert_eq!(build(&mut ctx), Some(0));
+        ctx.input_special_key(ui::SpecialKey::Up);
+        assert_eq!(build(&mut ctx), Some(2));
+    }
+    #[test]
+    fn test_spinner() {
+        let mut ctx = ui::Context::new();
+        let build = |ctx: &mut ui::Context| {
+            ctx.begin();
+            let ret = ctx.spinner("strength", 4, 0, 100, 5, 40);
+            ctx.end();
+            ret
+        };
+        assert_eq!(build(&mut ctx), (40, false));
+        let mut rend = AsciiRenderer::new();
+        ctx.render(&mut rend);
+        assert!(rend.assert("- [ 40 ] +", 0, 0));
+        // + and - signs
+        ctx.input_mouse_pos(9.0, 0.0);
+        ctx.input_mouse_down(ui::MOUSE_BUTTON_LEFT);
+        ctx.input_mouse_up(ui::MOUSE_BUTTON_LEFT);
+        assert_eq!(build(&mut ctx), (45, true));
+        assert_eq!(build(&mut ctx), (45, false));
+        // keyboard and wheel
+        ctx.input_special_key(ui::SpecialKey::PageUp);
+        assert_eq!(build(&mut ctx), (95, true));
+        ctx.input_special_key(ui::SpecialKey::PageUp);
+        assert_eq!(build(&mut ctx), (100, true));
+        ctx.input_mouse_wheel(-1);
+        assert_eq!(build(&mut ctx), (95, true));
+        // drag over the number
+        ctx.input_mouse_pos(4.0, 0.0);
+        ctx.input_mouse_down(ui::MOUSE_BUTTON_LEFT);
+        build(&mut ctx);
+        ctx.input_mouse_pos(2.0, 0.0);
+        assert_eq!(build(&mut ctx), (85, true));
+        ctx.input_mouse_up(ui::MOUSE_BUTTON_LEFT);
+        build(&mut ctx);
+        // typing a number, snapped to the step
+        ctx.input_text("7".to_owned());
+        assert_eq!(build(&mut ctx), (85, false));
+        ctx.input_special_key(ui::SpecialKey::Enter);
+        assert_eq!(build(&mut ctx), (5, true));
+        // floats
+        ctx.begin();
+        assert_eq!(ctx.spinner("speed", 4, 0.0, 1.0, 0.25, 0.5), (0.5, false));
+        ctx.end();
+        let mut rend = AsciiRenderer::new();
+        ctx.render(&mut rend);
+        assert!(rend.assert("- [0.50] +", 0, 0));
+    }
+    #[test]
+    fn test_slider() {
+        let mut ctx = ui::Context::new();
+        let build = |ctx: &mut ui::Context| {
+            ctx.begin();
+            let value = ctx.show_value().fslider("f", 11, 0.0, 10.0, 5.0);
+            let ivalue = ctx.step(2.0).islider_vertical("i", 5, 0, 8, 4);
+            ctx.end();
+            (value, ivalue)
+        };
+        assert_eq!(build(&mut ctx), (5.0, 4));
+        let mut rend = AsciiRenderer::new();
+        ctx.render(&mut rend);
+        assert!(rend.assert("   5.00    ", 0, 0));
+        // click on the track
+        ctx.input_mouse_pos(8.0, 0.0);
+        ctx.input_mouse_down(ui::MOUSE_BUTTON_LEFT);
+        assert_eq!(build(&mut ctx), (8.0, 4));
+        // drag from the click position, even outside of the slider
+        ctx.input_mouse_pos(3.0, 0.0);
+        assert_eq!(build(&mut ctx), (3.0, 4));
+        ctx.input_mouse_pos(20.0, 3.0);
+        assert_eq!(build(&mut ctx), (10.0, 4));
+        ctx.input_mouse_up(ui::MOUSE_BUTTON_LEFT);
+        build(&mut ctx);
+        // keyboard
+        ctx.input_mouse_pos(10.0, 0.0);
+        ctx.input_mouse_down(ui::MOUSE_BUTTON_LEFT);
+        ctx.input_mouse_up(ui::MOUSE_BUTTON_LEFT);
+        assert_eq!(build(&mut ctx), (10.0, 4));
+        ctx.input_special_key(ui::SpecialKey::Left);
+        assert_eq!(build(&mut ctx), (9.0, 4));
+        ctx.input_special_key(ui::SpecialKey::PageDown);
+        assert_eq!(build(&mut ctx), (8.0, 4));
+        // vertical slider, max at the top, snapped to the step
+        ctx.input_mouse_pos(0.0, 2.0);
+        ctx.input_mouse_down(ui::MOUSE_BUTTON_LEFT);
+        ctx.input_mouse_up(ui::MOUSE_BUTTON_LEFT);
+        assert_eq!(build(&mut ctx), (8.0, 6));
+        ctx.input_special_key(ui::SpecialKey::Up);
+        assert_eq!(build(&mut ctx), (8.0, 8));
+        // integer slider longer than its range
+        let mut ctx = ui::Context::new();
+        let build = |ctx: &mut ui::Context| {
+            ctx.begin();
+            let value = ctx.islider("i", 20, 0, 5, 0);
+            ctx.end();
+            value
+        };
+        build(&mut ctx);
+        ctx.input_special_key(ui::SpecialKey::Tab);
+        build(&mut ctx);
+        ctx.input_special_key(ui::SpecialKey::Right);
+        assert_eq!(build(&mut ctx), 1);
+        ctx.input_special_key(ui::SpecialKey::Right);
+        assert_eq!(build(&mut ctx), 2);
+        ctx.input_special_key(ui::SpecialKey::PageUp);
+        assert_eq!(build(&mut ctx), 3);
+        ctx.input_special_key(ui::SpecialKey::PageDown);
+        assert_eq!(build(&mut ctx), 2);
+    }
+    #[test]
+    fn test_range_slider() {
+        let mut ctx = ui::Context::new();
+        let build = |ctx: &mut ui::Context| {
+            ctx.begin();
+            let range = ctx.show_value().irange_slider("level", 11, 0, 10, (2, 6));
+            ctx.end();
+            range
+        };
+        assert_eq!(build(&mut ctx), (2, 6));
+        let mut rend = AsciiRenderer::new();
+        ctx.render(&mut rend);
+        assert!(rend.assert("   2 - 6   ", 0, 0));
+        // a click moves the closest handle
+        ctx.input_mouse_pos(8.0, 0.0);
+        ctx.input_mouse_down(ui::MOUSE_BUTTON_LEFT);
+        assert_eq!(build(&mut ctx), (2, 8));
+        // the handles can't cross
+        ctx.input_mouse_pos(0.0, 0.0);
+        assert_eq!(build(&mut ctx), (2, 2));
+        ctx.input_mouse_up(ui::MOUSE_BUTTON_LEFT);
+        build(&mut ctx);
+        ctx.input_mouse_pos(1.0, 0.0);
+        ctx.input_mouse_down(ui::MOUSE_BUTTON_LEFT);
+        ctx.input_mouse_up(ui::MOUSE_BUTTON_LEFT);
+        assert_eq!(build(&mut ctx), (1, 2));
+        ctx.input_special_key(ui::SpecialKey::Right);
+        assert_eq!(build(&mut ctx), (2, 2));
+        ctx.input_special_key(ui::SpecialKey::Right);
+        assert_eq!(build(&mut ctx), (2, 2));
+        // each slider remembers its own grabbed handle
+        let mut ctx = ui::Context::new();
+        let build = |ctx: &mut ui::Context| {
+            ctx.begin();
+            let a = ctx.irange_slider("a", 11, 0, 10, (2, 6));
+            let b = ctx.irange_slider("b", 11, 0, 10, (2, 6));
+            ctx.end();
+            (a, b)
+        };
+        build(&mut ctx);
+        ctx.input_mouse_pos(8.0, 0.0);
+        ctx.input_mouse_down(ui::MOUSE_BUTTON_LEFT);
+        ctx.input_mouse_up(ui::MOUSE_BUTTON_LEFT);
+        assert_eq!(build(&mut ctx), ((2, 8), (2, 6)));
+        ctx.input_special_key(ui::SpecialKey::Tab);
+        build(&mut ctx);
+        ctx.input_special_key(ui::SpecialKey::Right);
+        assert_eq!(build(&mut ctx), ((2, 8), (3, 6)));
+    }
+    #[test]
+    fn test_table() {
+        let mut ctx = ui::Context::new();
+        let columns = [ui::Column::new("Name", 6), ui::Column::new("Qty", 3)];
+        let click = |ctx: &mut ui::Context, x: f32, y: f32, modifiers: usize| {
+            ctx.input_key_modifiers(modifiers);
+            ctx.input_mouse_pos(x, y);
+            ctx.input_mouse_down(ui::MOUSE_BUTTON_LEFT);
+            ctx.input_mouse_up(ui::MOUSE_BUTTON_LEFT);
+        };
+        let build = |ctx: &mut ui::Context| {
+            ctx.begin();
+            let rows = ctx.table_begin("inventory", &columns, 4, 10);
+            let id = ctx.last_id();
+            let mut built = Vec::new();
+            for row in rows {
+                ctx.table_row(row);
+                ctx.label(&format!("item{}", row));
+                ctx.label(&format!("{}", row));
+                built.push(row);
+            }
+            let sort = ctx.table_end();
+            ctx.end();
+            (built, sort, ctx.table_selection(id))
+        };
+        let (built, sort, selection) = build(&mut ctx);
+        // only the visible rows are built
+        assert_eq!((built, sort, selection), (vec![0, 1, 2], None, vec![]));
+        let mut rend = AsciiRenderer::new();
+        ctx.render(&mut rend);
+        assert!(rend.assert("Name  |Qty|", 0, 0));
+        assert!(rend.assert("item1  1", 0, 2));
+        // sort
+        click(&mut ctx, 8.0, 0.0, 0);
+        let sort = Some(ui::TableSort {
+            column: 1,
+            ascending: true,
+        });
+        assert_eq!(build(&mut ctx).1, sort);
+        let mut rend = AsciiRenderer::new();
+        ctx.render(&mut rend);
+        assert!(rend.assert("Qt^", 7, 0));
+        // selection
+        click(&mut ctx, 1.0, 2.0, 0);
+        assert_eq!(build(&mut ctx).2, vec![1]);
+        click(&mut ctx, 1.0, 3.0, ui::KEY_MOD_SHIFT);
+        assert_eq!(build(&mut ctx).2, vec![1, 2]);
+        click(&mut ctx, 1.0, 1.0, ui::KEY_MOD_CTRL);
+        assert_eq!(build(&mut ctx).2, vec![0, 1, 2]);
+        ctx.input_key_modifiers(0);
+        ctx.input_special_key(ui::SpecialKey::Down);
+        assert_eq!(build(&mut ctx).2, vec![1]);
+        ctx.input_special_key(ui::SpecialKey::End);
+        build(&mut ctx);
+        assert_eq!(build(&mut ctx), (vec![7, 8, 9], None, vec![9]));
+        // column resize
+        ctx.input_mouse_pos(6.0, 0.0);
+        ctx.input_mouse_down(ui::MOUSE_BUTTON_LEFT);
+        build(&mut ctx);
+        ctx.input_mouse_pos(8.0, 0.0);
+        build(&mut ctx);
+        ctx.input_mouse_up(ui::MOUSE_BUTTON_LEFT);
+        build(&mut ctx);
+        let mut rend = AsciiRenderer::new();
+        ctx.render(&mut rend);
+        assert!(rend.assert("Name    |Qt^|", 0, 0));
+    }
+    #[test]
+    fn test_tree() {
+        let mut ctx = ui::Context::new();
+        let build = |ctx: &mut ui::Context| {
+            ctx.begin();
+            ctx.tree_begin("tree");
+            let mut ids = vec![ctx.last_id()];
+            let mut built = 0;
+            if ctx.tree_node_begin("world", "world", true) {
+                ids.push(ctx.last_id());
+                let open = ctx.tree_node_begin("a", "a", false);
+                ids.push(ctx.last_id());
+                if open {
+                    ctx.tree_leaf("x", "x");
+                    built += 1;
+                }
+                ctx.tree_node_end();
+                ctx.tree_leaf("b", "b");
+                ids.push(ctx.last_id());
+            }
+            ctx.tree_node_end();
+            // a second top level node of the same tree
+            let sky_selected = ctx.tree_leaf("sky", "sky");
+            ids.push(ctx.last_id());
+            ctx.tree_end();
+            ctx.end();
+            (ids, built, sky_selected)
+        };
+        build(&mut ctx);
+        let (ids, _, _) = build(&mut ctx);
+        let mut rend = AsciiRenderer::new();
+        ctx.render(&mut rend);
+        assert!(rend.assert("> world", 0, 0));
+        assert!(rend.assert("├─> a", 0, 1));
+        assert!(rend.assert("└── b", 0, 2));
+        // click selects, Right expands
+        ctx.input_mouse_pos(4.0, 1.0);
+        ctx.input_mouse_down(ui::MOUSE_BUTTON_LEFT);
+        ctx.input_mouse_up(ui::MOUSE_BUTTON_LEFT);
+        build(&mut ctx);
+        assert_eq!(ctx.tree_selection(ids[0]), Some(ids[2]));
+        ctx.input_special_key(ui::SpecialKey::Right);
+        build(&mut ctx);
+        build(&mut ctx);
+        let mut rend = AsciiRenderer::new();
+        ctx.render(&mut rend);
+        assert!(rend.assert("│ └── x", 0, 2));
+        // Left collapses, then selects the parent
+        ctx.input_special_key(ui::SpecialKey::Left);
+        assert_eq!(build(&mut ctx).1, 0);
+        ctx.input_special_key(ui::SpecialKey::Left);
+        build(&mut ctx);
+        build(&mut ctx);
+        assert_eq!(ctx.tree_selection(ids[0]), Some(ids[1]));
+        ctx.input_special_key(ui::SpecialKey::Down);
+        build(&mut ctx);
+        ctx.input_special_key(ui::SpecialKey::Down);
+        build(&mut ctx);
+        build(&mut ctx);
+        assert_eq!(ctx.tree_selection(ids[0]), Some(ids[3]));
+        // the top level nodes share the selection of the tree
+        ctx.input_special_key(ui::SpecialKey::Down);
+        build(&mut ctx);
+        let (_, _, sky_selected) = build(&mut ctx);
+        assert!(sky_selected);
+        assert_eq!(ctx.tree_selection(ids[0]), Some(ids[4]));
+    }
+    #[test]
+    fn test_list_box() {
+        let mut ctx = ui::Context::new();
+        let items: Vec<String> = (0..1000).map(|i| format!("monster{:03}", i)).collect();
+        let build = |ctx: &mut ui::Context| {
+            ctx.begin();
+            ctx.list_box("monsters", &items, 12, 4, true);
+            let id = ctx.last_id();
+            ctx.end();
+            ctx.list_box_selection(id)
+        };
+        build(&mut ctx);
+        let mut rend = AsciiRenderer::new();
+        ctx.render(&mut rend);
+        assert!(rend.assert("monster003", 0, 3));
+        // only the visible rows are drawn
+        let texts = ctx
+            .get_render_commands()
+            .iter()
+            .filter(|(_, c)| matches!(c, ui::Command::Text(..)))
+            .count();
+        assert_eq!(texts, 4);
+        let click = |ctx: &mut ui::Context, y: f32, modifiers: usize| {
+            ctx.input_key_modifiers(modifiers);
+            ctx.input_mouse_pos(1.0, y);
+            ctx.input_mouse_down(ui::MOUSE_BUTTON_LEFT);
+            ctx.input_mouse_up(ui::MOUSE_BUTTON_LEFT);
+        };
+        click(&mut ctx, 1.0, 0);
+        assert_eq!(build(&mut ctx), vec![1]);
+        click(&mut ctx, 3.0, ui::KEY_MOD_SHIFT);
+        assert_eq!(build(&mut ctx), vec![1, 2, 3]);
+        click(&mut ctx, 2.0, ui::KEY_MOD_CTRL);
+        assert_eq!(build(&mut ctx), vec![1, 3]);
+        ctx.input_key_modifiers(0);
+        ctx.input_special_key(ui::SpecialKey::PageDown);
+        assert_eq!(build(&mut ctx), vec![6]);
+        ctx.input_special_key(ui::SpecialKey::End);
+        assert_eq!(build(&mut ctx), vec![999]);
+        // type-ahead
+        ctx.input_text("monster04".to_owned());
+        assert_eq!(build(&mut ctx), vec![40]);
+        ctx.input_text("2".to_owned());
+        assert_eq!(build(&mut ctx), vec![42]);
+        let mut rend = AsciiRenderer::new();
+        ctx.render(&mut rend);
+        assert!(rend.assert("monster042", 0, 2));
+    }
+    #[test]
+    fn test_message_log() {
+        let mut ctx = ui::Context::new();
+        let mut entries = vec![
+            "Welcome to the #[red]dungeon of doom adventurer#[]".to_owned(),
+            "You hit the rat".to_owned(),
+            "You hit the rat".to_owned(),
+            "You hit the rat".to_owned(),
+        ];
+        ctx.register_color("red", (255, 0, 0, 255));
+        let build = |ctx: &mut ui::Context, entries: &[String]| {
+            ctx.begin();
+            ctx.message_log("log", entries, 21, 3);
+            ctx.end();
+            let mut rend = AsciiRenderer::new();
+            ctx.render(&mut rend);
+            rend
+        };
+        let rend = build(&mut ctx, &entries);
+        // word wrap, the color is reopened on the next line
+        assert!(rend.assert("dungeon of doom", 0, 0));
+        assert!(rend.assert("adventurer", 0, 1));
+        assert!(rend.assert("You hit the rat x3", 0, 2));
+        // pinned to the bottom
+        entries.push("The rat dies".to_owned());
+        let rend = build(&mut ctx, &entries);
+        assert!(rend.assert("The rat dies", 0, 2));
+        // scrolled up, the view stays on the same messages
+        ctx.input_mouse_pos(1.0, 1.0);
+        ctx.input_mouse_wheel(1);
+        build(&mut ctx, &entries);
+        entries.push("You feel better".to_owned());
+        let rend = build(&mut ctx, &entries);
+        assert!(rend.assert("Welcome to the", 0, 0));
+        // old messages keep their colors, faded toward the background
+        assert!(rend.assert("dungeon of doom", 0, 1));
+        let mut faded = None;
+        ctx.visit_visible_commands(|c| match c {
+            ui::Command::Text(txt, _, col) if txt == "dungeon of doom" => faded = Some(*col),
+            _ => (),
+        });
+        let faded = faded.unwrap();
+        assert!(faded.0 > faded.1 && faded != (255, 0, 0, 255));
+    }
+    #[test]
+    fn test_paragraph() {
+        let mut rend = AsciiRenderer::new();
+        let mut ctx = ui::Context::new();
+        ctx.begin();
+        ctx.paragraph(
+            "A rusty sword.\nIt looks like it could break at any moment",
+            12,
+        );
+        ctx.paragraph("Forged by the dwarves", 12)
+            .align(ui::TextAlign::Right);
+        ctx.label("below");
+        ctx.end();
+        ctx.render(&mut rend);
+        assert!(rend.assert("A rusty", 0, 0));
+        assert!(rend.assert("sword.", 0, 1));
+        assert!(rend.assert("It looks", 0, 2));
+        assert!(rend.assert("like it", 0, 3));
+        assert!(rend.assert("could break", 0, 4));
+        assert!(rend.assert("at any", 0, 5));
+        assert!(rend.assert("moment", 0, 6));
+        assert!(rend.assert("   Forged by", 0, 7));
+        assert!(rend.assert("the dwarves", 1, 8));
+        // the height is reported to the layout
+        assert!(rend.assert("below", 0, 9));
+        assert_eq!(
+            ui::wrap_text("#[red]Hello world#[] !", 5),
+            vec!["#[red]Hello", "#[red]world#[]", "!"]
+        );
+    }
+    #[test]
+    fn test_color_markup() {
+        let mut ctx = ui::Context::new();
+        let red = (255, 0, 0, 255);
+        let text = ctx.get_color(ui::ColorCode::Text);
+        ctx.register_color("red", red);
+        ctx.begin();
+        ctx.label_color("a #[red]red#[] #[unknown]word");
+        ctx.end();
+        assert_eq!(ui::text_color_len("a #[red]red#[] #[unknown]word"), 10);
+        let texts: Vec<(String, ui::Color)> = ctx
+            .get_render_commands()
+            .iter()
+            .filter_map(|(_, c)| match c {
+                ui::Command::Text(txt, _, col) => Some((txt.clone(), *col)),
+                _ => None,
+            })
+            .collect();
+        assert_eq!(
+            texts,
+            vec![
+                ("a ".to_owned(), text),
+                ("red".to_owned(), red),
+                (" ".to_owned(), text),
+                ("word".to_owned(), text),
+            ]
+        );
+        let mut rend = AsciiRenderer::new();
+        ctx.render(&mut rend);
+        assert!(rend.assert("a red word", 0, 0));
+    }
+    #[test]
+    fn test_animation() {
+        let mut ctx = ui::Context::new();
+        let build = |ctx: &mut ui::Context, dt: f32, target: f32| {
+            ctx.input_time(dt);
+            ctx.begin();
+            let value = ctx.tween(1, target, 1.0, ui::Easing::Linear);
+            let open = ctx
+                .dropdown_panel_begin("panel", "panel", false, 10, 4)
+                .active();
+            if open {
+                for i in 0..4 {
+                    ctx.label(&format!("line{}", i));
+                }
+            }
+            ctx.dropdown_panel_end();
+            ctx.label("below");
+            ctx.end();
+            let mut rend = AsciiRenderer::new();
+            ctx.render(&mut rend);
+            (value, rend)
+        };
+        assert_eq!(build(&mut ctx, 0.5, 0.0).0, 0.0);
+        assert_eq!(build(&mut ctx, 0.5, 10.0).0, 0.0);
+        assert_eq!(build(&mut ctx, 0.5, 10.0).0, 5.0);
+        assert_eq!(build(&mut ctx, 0.5, 10.0).0, 10.0);
+        // the panel opens progressively
+        ctx.input_mouse_pos(1.0, 0.0);
+        ctx.input_mouse_down(ui::MOUSE_BUTTON_LEFT);
+        ctx.input_mouse_up(ui::MOUSE_BUTTON_LEFT);
+        let rend = build(&mut ctx, 0.1, 10.0).1;
+        assert!(rend.assert("below", 0, 1));
+        let rend = build(&mut ctx, 0.075, 10.0).1;
+        assert!(rend.assert("line1", 0, 2));
+        assert!(!rend.assert("line2", 0, 3));
+        assert!(rend.assert("below", 0, 3));
+        let rend = build(&mut ctx, 1.0, 10.0).1;
+        assert!(rend.assert("line3", 0, 4));
+        assert!(rend.assert("below", 0, 5));
+        // a week later, the animations still run at the frame rate
+        build(&mut ctx, 600_000.0, 10.0);
+        build(&mut ctx, 1.0 / 60.0, 0.0);
+        assert!(build(&mut ctx, 1.0 / 60.0, 0.0).0 < 10.0);
+        // the finished animations of the widgets no longer displayed are forgotten
+        ctx.begin();
+        ctx.tween(2, 1.0, 0.5, ui::Easing::Linear);
+        ctx.end();
+        assert!(ctx.tweens.contains_key(&2));
+        ctx.input_time(1.0);
+        ctx.begin();
+        ctx.end();
+        assert!(!ctx.tweens.contains_key(&2));
+    }
 }
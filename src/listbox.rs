@@ -0,0 +1,124 @@
+use crate::{ColorCode, Context, Coord, Id, Rect, RowSelection, TextAlign};
+
+/// time in seconds after which the type-ahead search restarts
+const TYPE_AHEAD_DELAY: f64 = 1.0;
+
+impl Context {
+    // =======================================================
+    //
+    // List box
+    //
+    // =======================================================
+    /// a scrollable list of selectable items. Only the visible items are drawn,
+    /// so it can contain thousands of entries.
+    /// A click selects an item. If multi is true, ctrl+click toggles an item,
+    /// shift+click selects a range and Space toggles the current item.
+    /// When the list has the focus, Up/Down/PageUp/PageDown/Home/End move the selection
+    /// and typing jumps to the first item starting with the typed letters.
+    /// Returns true if the selection has changed this frame.
+    /// ctx.last_id() returns the list id right after this call.
+    pub fn list_box<S: AsRef<str>>(
+        &mut self,
+        id: &str,
+        items: &[S],
+        width: Coord,
+        height: Coord,
+        multi: bool,
+    ) -> bool {
+        self.try_commit();
+        let list_id = self.generate_id(id);
+        let r = self.next_rectangle(width, height);
+        let body = Rect::new(r.x, r.y, r.w - 1, r.h);
+        self.update_control(list_id, &r, false);
+        let focus = self.focus == list_id;
+        let hover = self.hover == list_id;
+        let state = self.list_box_state.entry(list_id).or_default();
+        let mut rows = std::mem::take(&mut state.rows);
+        rows.row_count = items.len();
+        let old_selection = rows.selection.clone();
+        self.update_row_selection(&mut rows, body, hover, focus, multi);
+        if focus && !self.text_input.is_empty() {
+            self.type_ahead(list_id, items, &mut rows, body.h.max(1) as usize);
+        }
+        rows.selection.retain(|row| *row < items.len());
+        self.prefix_id(id);
+        rows.offset = self.scrollbar(
+            "scrollbar",
+            Rect::new(r.x + r.w - 1, r.y, 1, r.h),
+            true,
+            rows.offset as Coord,
+            items.len() as Coord,
+            r.h,
+        ) as usize;
+        self.id_prefix.pop();
+        let changed = rows.selection != old_selection;
+        self.draw_list_box(body, items, &rows, focus);
+        self.list_box_state.get_mut(&list_id).unwrap().rows = rows;
+        self.last_id = list_id;
+        changed
+    }
+    /// the selected items of a list box, in increasing order
+    pub fn list_box_selection(&self, list_id: Id) -> Vec<usize> {
+        self.list_box_state
+            .get(&list_id)
+            .map_or(Vec::new(), |state| state.rows.sorted_selection())
+    }
+    pub fn set_list_box_selection(&mut self, list_id: Id, selection: &[usize]) {
+        let rows = &mut self.list_box_state.entry(list_id).or_default().rows;
+        rows.selection = selection.iter().copied().collect();
+        if let Some(first) = selection.first() {
+            rows.cursor = *first;
+            rows.anchor = *first;
+        }
+    }
+    /// jumps to the first item starting with the letters typed recently
+    fn type_ahead<S: AsRef<str>>(
+        &mut self,
+        list_id: Id,
+        items: &[S],
+        rows: &mut RowSelection,
+        page: usize,
+    ) {
+        let time = self.time;
+        let state = self.list_box_state.get_mut(&list_id).unwrap();
+        if time - state.typed_time > TYPE_AHEAD_DELAY {
+            state.typed.clear();
+        }
+        state.typed_time = time;
+        state.typed += &self.text_input.to_lowercase();
+        let typed = &state.typed;
+        if let Some(row) = items
+            .iter()
+            .position(|item| item.as_ref().to_lowercase().starts_with(typed.as_str()))
+        {
+            rows.select(row, false, false);
+            rows.scroll_to(row, page);
+        }
+    }
+    fn draw_list_box<S: AsRef<str>>(
+        &mut self,
+        body: Rect,
+        items: &[S],
+        rows: &RowSelection,
+        focus: bool,
+    ) {
+        let back = self.get_color(ColorCode::Background);
+        let fore = self.get_color(ColorCode::Text);
+        self.draw_rect(body, back);
+        let visible = rows.offset..(rows.offset + body.h.max(0) as usize).min(items.len());
+        for row in visible {
+            let row_rect = Rect::new(body.x, body.y + (row - rows.offset) as Coord, body.w, 1);
+            let row_back = if rows.selection.contains(&row) {
+                Some(self.get_color(ColorCode::TextSelection))
+            } else if row == rows.cursor && focus {
+                Some(self.get_color(ColorCode::ButtonBackgroundFocus))
+            } else {
+                None
+            };
+            if let Some(row_back) = row_back {
+                self.draw_rect(row_rect, row_back);
+            }
+            self.draw_text(row_rect, items[row].as_ref(), TextAlign::Left, fore);
+        }
+    }
+}
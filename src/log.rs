@@ -0,0 +1,108 @@
+use crate::{color_blend, wrap_text, ColorCode, Context, Coord, Rect, TextAlign};
+
+/// number of lines scrolled by a mouse wheel notch
+const LOG_WHEEL_STEP: Coord = 3;
+/// the most recent messages keep their colors
+const LOG_FRESH_MESSAGES: usize = 3;
+/// fading applied to each older message
+const LOG_FADE_STEP: f32 = 0.1;
+const LOG_MAX_FADE: f32 = 0.6;
+
+impl Context {
+    // =======================================================
+    //
+    // Message log
+    //
+    // =======================================================
+    /// a scrolling history of messages, the most recent at the bottom.
+    /// The messages can contain #[color] markup (see label_color) and are word wrapped.
+    /// Consecutive identical messages are displayed once with a repeat count.
+    /// The view stays on the last message unless the player scrolled up with the mouse wheel
+    /// or the scrollbar. Older messages fade toward the Background color.
+    pub fn message_log<S: AsRef<str>>(
+        &mut self,
+        id: &str,
+        entries: &[S],
+        width: Coord,
+        height: Coord,
+    ) {
+        self.try_commit();
+        let log_id = self.generate_id(id);
+        let r = self.next_rectangle(width, height);
+        let body = Rect::new(r.x, r.y, r.w - 1, r.h);
+        let lines = log_lines(entries, body.w.max(1) as usize);
+        let total = lines.len() as Coord;
+        self.update_mouse_control(log_id, &r, false);
+        let hover = self.hover == log_id;
+        let state = self.message_log_state.entry(log_id).or_default();
+        if state.offset > 0 && total > state.line_count {
+            // keep the same messages in view when new ones arrive
+            state.offset += total - state.line_count;
+        }
+        state.line_count = total;
+        if hover && self.mouse_wheel != 0 {
+            state.offset += self.mouse_wheel * LOG_WHEEL_STEP;
+            self.mouse_wheel = 0;
+        }
+        let max_offset = (total - body.h).max(0);
+        let from_bottom = state.offset.clamp(0, max_offset);
+        self.prefix_id(id);
+        // the scrollbar offset starts from the top
+        let top = self.scrollbar(
+            "scrollbar",
+            Rect::new(r.x + r.w - 1, r.y, 1, r.h),
+            true,
+            max_offset - from_bottom,
+            total,
+            body.h,
+        );
+        self.id_prefix.pop();
+        self.message_log_state.get_mut(&log_id).unwrap().offset = max_offset - top;
+        let back = self.get_color(ColorCode::Background);
+        let fore = self.get_color(ColorCode::Text);
+        self.draw_rect(body, back);
+        for (y, (line, age)) in lines
+            .iter()
+            .skip(top as usize)
+            .take(body.h.max(0) as usize)
+            .enumerate()
+        {
+            let line_rect = Rect::new(body.x, body.y + y as Coord, body.w, 1);
+            let fade = if *age < LOG_FRESH_MESSAGES {
+                0.0
+            } else {
+                ((*age + 1 - LOG_FRESH_MESSAGES) as f32 * LOG_FADE_STEP).min(LOG_MAX_FADE)
+            };
+            let spans: Vec<_> = self
+                .color_spans(line, fore)
+                .into_iter()
+                .map(|(text, col)| (text, color_blend(col, back, fade)))
+                .collect();
+            self.draw_color_spans(line_rect, &spans, TextAlign::Left);
+        }
+    }
+}
+
+/// the wrapped lines of the log with the age of their message (0 for the most recent).
+/// Repeated messages are collapsed
+fn log_lines<S: AsRef<str>>(entries: &[S], width: usize) -> Vec<(String, usize)> {
+    let mut messages: Vec<(&str, usize)> = Vec::new();
+    for entry in entries.iter().map(|entry| entry.as_ref()) {
+        match messages.last_mut() {
+            Some((last, count)) if *last == entry => *count += 1,
+            _ => messages.push((entry, 1)),
+        }
+    }
+    let count = messages.len();
+    let mut lines = Vec::new();
+    for (i, (text, repeat)) in messages.iter().enumerate() {
+        let text = if *repeat > 1 {
+            format!("{} x{}", text, repeat)
+        } else {
+            (*text).to_owned()
+        };
+        let age = count - 1 - i;
+        lines.extend(wrap_text(&text, width).into_iter().map(|line| (line, age)));
+    }
+    lines
+}
@@ -0,0 +1,166 @@
+use unicode_segmentation::UnicodeSegmentation;
+
+use crate::{Color, Command, Context, Coord, Pos, Rect, TextAlign};
+
+/// a piece of a text with #[color] markup
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub(crate) enum MarkupToken<'a> {
+    Text(&'a str),
+    /// #[name] starts a color, #[] goes back to the default color
+    Color(&'a str),
+}
+
+/// splits a text into its markup tags and its text runs.
+/// An unterminated tag is considered as text
+pub(crate) fn markup_tokens(txt: &str) -> impl Iterator<Item = MarkupToken<'_>> + '_ {
+    let mut rest = txt;
+    std::iter::from_fn(move || {
+        if rest.is_empty() {
+            return None;
+        }
+        let tag = rest
+            .find("#[")
+            .and_then(|start| rest[start..].find(']').map(|end| (start, start + end)));
+        Some(match tag {
+            Some((0, end)) => {
+                let name = &rest[2..end];
+                rest = &rest[end + 1..];
+                MarkupToken::Color(name)
+            }
+            Some((start, _)) => {
+                let text = &rest[..start];
+                rest = &rest[start..];
+                MarkupToken::Text(text)
+            }
+            None => MarkupToken::Text(std::mem::take(&mut rest)),
+        })
+    })
+}
+
+/// number of characters of a text once the #[color] markup is removed
+pub fn text_color_len(txt: &str) -> usize {
+    markup_tokens(txt)
+        .map(|token| match token {
+            MarkupToken::Text(text) => text.chars().count(),
+            MarkupToken::Color(_) => 0,
+        })
+        .sum()
+}
+
+/// splits a text with color markup into lines of at most width visible characters.
+/// Lines are broken on the unicode word boundaries and on the newlines.
+/// A color still open at the end of a line is reopened at the start of the next one
+pub(crate) fn wrap_text(text: &str, width: usize) -> Vec<String> {
+    let reopen = |color: &Option<&str>| color.map_or(String::new(), |c| format!("#[{}]", c));
+    let mut lines = Vec::new();
+    let mut color = None;
+    for paragraph in text.split('\n') {
+        let mut line = reopen(&color);
+        let mut len = 0;
+        // spaces are only added if the next word fits on the line
+        let mut spaces = String::new();
+        for token in markup_tokens(paragraph) {
+            let segment = match token {
+                MarkupToken::Color(name) => {
+                    line += &format!("#[{}]", name);
+                    color = Some(name).filter(|name| !name.is_empty());
+                    continue;
+                }
+                MarkupToken::Text(segment) => segment,
+            };
+            for word in segment.split_word_bounds() {
+                if word.trim().is_empty() {
+                    spaces += word;
+                    continue;
+                }
+                let word_len = word.chars().count();
+                let spaces_len = spaces.chars().count();
+                if len > 0 && len + spaces_len + word_len > width {
+                    lines.push(std::mem::replace(&mut line, reopen(&color)));
+                    len = 0;
+                } else {
+                    line += &spaces;
+                    len += spaces_len;
+                }
+                spaces.clear();
+                for c in word.chars() {
+                    if len == width {
+                        // words longer than the line are cut
+                        lines.push(std::mem::replace(&mut line, reopen(&color)));
+                        len = 0;
+                    }
+                    line.push(c);
+                    len += 1;
+                }
+            }
+        }
+        lines.push(line);
+    }
+    lines
+}
+
+impl Context {
+    // =======================================================
+    //
+    // Color markup
+    //
+    // =======================================================
+    /// defines a color name usable in the #[name] markup of label_color, paragraph,
+    /// tooltips and message logs
+    pub fn register_color(&mut self, name: &str, col: Color) {
+        self.color_names.insert(name.to_owned(), col);
+    }
+    /// the colored runs of a text with #[color] markup. The text outside of any markup
+    /// and the unknown color names use the default color
+    pub(crate) fn color_spans<'a>(&self, txt: &'a str, default: Color) -> Vec<(&'a str, Color)> {
+        let mut col = default;
+        let mut spans = Vec::new();
+        for token in markup_tokens(txt) {
+            match token {
+                MarkupToken::Text(text) => spans.push((text, col)),
+                MarkupToken::Color(name) => {
+                    col = self.color_names.get(name).copied().unwrap_or(default)
+                }
+            }
+        }
+        spans
+    }
+    /// draws a text with color markup as one text command per colored run
+    pub(crate) fn draw_text_color(&mut self, r: Rect, txt: &str, align: TextAlign, col: Color) {
+        let spans = self.color_spans(txt, col);
+        self.draw_color_spans(r, &spans, align);
+    }
+    /// draws colored runs of text one after the other
+    pub(crate) fn draw_color_spans(&mut self, r: Rect, spans: &[(&str, Color)], align: TextAlign) {
+        let len = spans
+            .iter()
+            .map(|(text, _)| text.chars().count())
+            .sum::<usize>() as Coord;
+        // visible part of the text
+        let (x, skip, count) = match align {
+            TextAlign::Left => (r.x, 0, len.min(r.w)),
+            TextAlign::Right if len > r.w => (r.x, len - r.w, r.w),
+            TextAlign::Right => (r.x + r.w - len, 0, len),
+            TextAlign::Center if len > r.w => (r.x, (len - r.w) / 2, r.w),
+            TextAlign::Center => (r.x + r.w / 2 - len / 2, 0, len),
+        };
+        let mut pos = 0;
+        let mut x = x;
+        for (text, col) in spans.iter().copied() {
+            let text_len = text.chars().count() as Coord;
+            let start = (skip - pos).max(0);
+            let end = (skip + count - pos).min(text_len);
+            pos += text_len;
+            if end <= start {
+                continue;
+            }
+            let run: String = text
+                .chars()
+                .skip(start as usize)
+                .take((end - start) as usize)
+                .collect();
+            self.push_command(Command::Text(run, Pos { x, y: r.y }, col));
+            x += end - start;
+        }
+    }
+}
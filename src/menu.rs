@@ -0,0 +1,406 @@
+use crate::{
+    ColorCode, Command, Context, Coord, Id, Layer, MenuEntry, MenuLevel, Pos, Rect, SpecialKey,
+    TextAlign, KEY_MOD_ALT, KEY_MOD_CTRL, KEY_MOD_SHIFT, MOUSE_BUTTON_LEFT, MOUSE_BUTTON_RIGHT,
+    NULL_ID,
+};
+
+impl Context {
+    // =======================================================
+    //
+    // Menu bar
+    //
+    // =======================================================
+    /// starts a menu bar. Add menus with menu_begin/menu_end.
+    /// The entries are declared every frame, even when their menu is closed,
+    /// so that their accelerators keep working.
+    pub fn menubar_begin(&mut self, id: &str, width: Coord) {
+        self.try_commit();
+        let r = self.next_rectangle(width, 1);
+        self.prefix_id(id);
+        self.menubar = r;
+        self.menubar_x = r.x;
+        if !self.menu_open.is_empty() && self.take_special_key(SpecialKey::Escape) {
+            self.menu_open.pop();
+        }
+        let back = self.get_color(ColorCode::ButtonBackground);
+        self.draw_rect(r, back);
+    }
+    pub fn menubar_end(&mut self) {
+        self.id_prefix.pop();
+    }
+    /// starts a menu. In the menu bar, it opens when its title is clicked.
+    /// Inside another menu, it's a submenu opening when its entry is hovered.
+    /// A letter preceded by & in the label is a mnemonic : Alt+letter opens a menu of the bar,
+    /// the letter alone activates an entry of the deepest open menu.
+    pub fn menu_begin(&mut self, label: &str) {
+        let id = self.generate_id(label);
+        let (text, mnemonic) = parse_mnemonic(label);
+        let depth = self.menu_stack.len();
+        let mut anchor = Rect::default();
+        let pos = if depth == 0 {
+            anchor = self.menu_title(id, &text, mnemonic);
+            Pos {
+                x: anchor.x,
+                y: anchor.y + 1,
+            }
+        } else {
+            let (r, activated) = self.add_menu_entry(
+                MenuEntry {
+                    label: text,
+                    shortcut: String::new(),
+                    checked: None,
+                    enabled: true,
+                    submenu: id,
+                },
+                mnemonic,
+            );
+            let mouse_pos: Pos = self.mouse_pos.into();
+            if self.menu_stack[depth - 1].open
+                && (activated || r.contains(mouse_pos))
+                && self.menu_open.get(depth) != Some(&id)
+            {
+                self.menu_open.truncate(depth);
+                self.menu_open.push(id);
+            }
+            Pos {
+                x: r.x + r.w,
+                y: r.y,
+            }
+        };
+        self.prefix_id(label);
+        self.push_menu_level(id, pos, anchor);
+    }
+    pub fn menu_end(&mut self) {
+        let level = self
+            .menu_stack
+            .pop()
+            .expect("menu_end must be called after menu_begin");
+        self.id_prefix.pop();
+        let label_width = level
+            .entries
+            .iter()
+            .map(|e| e.label.chars().count() as Coord)
+            .max()
+            .unwrap_or(0);
+        let shortcut_width = level
+            .entries
+            .iter()
+            .map(|e| e.shortcut.chars().count() as Coord)
+            .max()
+            .unwrap_or(0);
+        let shortcut_column = if shortcut_width > 0 {
+            shortcut_width + 2
+        } else {
+            0
+        };
+        // checkmark, label, shortcut and submenu arrow
+        let width = 2 + label_width + shortcut_column + 2;
+        self.menu_width.insert(level.id, width);
+        // an entry of the parent menu may have closed this menu
+        let depth = self.menu_stack.len();
+        let open = self.menu_open.get(depth) == Some(&level.id);
+        if !open || level.entries.is_empty() {
+            return;
+        }
+        let panel = Rect::new(
+            level.pos.x,
+            level.pos.y,
+            width,
+            level.entries.len() as Coord,
+        );
+        let mouse_pos: Pos = self.mouse_pos.into();
+        if depth == 0
+            && self.mouse_pressed != 0
+            && !level.anchor.contains(mouse_pos)
+            && !panel.contains(mouse_pos)
+            && !self.layer_areas[level.areas_start..]
+                .iter()
+                .any(|(_, r)| r.contains(mouse_pos))
+        {
+            // click outside the menu and its submenus
+            self.menu_open.clear();
+            return;
+        }
+        self.layer_areas.push((Layer::Popup, panel));
+        self.push_layer(Layer::Popup);
+        let start = self.commands.len();
+        self.draw_menu_panel(&level, panel);
+        self.pop_layer();
+        // draw the panel below its submenus
+        let panel_commands = self.commands.split_off(start);
+        self.commands
+            .splice(level.command_start..level.command_start, panel_commands);
+    }
+    /// adds an entry to the current menu.
+    /// The shortcut, for example "Ctrl+S", is displayed on the right side.
+    /// It's also an accelerator that works even when the menu is closed.
+    /// checked adds a checkmark. A disabled entry can't be activated.
+    /// returns true if the entry was activated
+    pub fn menu_entry(
+        &mut self,
+        label: &str,
+        shortcut: Option<&str>,
+        checked: Option<bool>,
+        enabled: bool,
+    ) -> bool {
+        let (text, mnemonic) = parse_mnemonic(label);
+        let shortcut = shortcut.unwrap_or("");
+        let (_, mut activated) = self.add_menu_entry(
+            MenuEntry {
+                label: text,
+                shortcut: shortcut.to_owned(),
+                checked,
+                enabled,
+                submenu: NULL_ID,
+            },
+            mnemonic,
+        );
+        if enabled && !shortcut.is_empty() && self.take_accelerator(shortcut) {
+            activated = true;
+        }
+        if activated {
+            self.menu_open.clear();
+        }
+        activated
+    }
+    /// returns true if the entry was activated
+    pub fn menu_item(&mut self, label: &str, shortcut: Option<&str>) -> bool {
+        self.menu_entry(label, shortcut, None, true)
+    }
+    /// an entry with a checkmark. returns true if the entry was activated
+    pub fn menu_check(&mut self, label: &str, shortcut: Option<&str>, checked: bool) -> bool {
+        self.menu_entry(label, shortcut, Some(checked), true)
+    }
+    // =======================================================
+    //
+    // Context menu
+    //
+    // =======================================================
+    /// starts a popup menu attached to the last widget.
+    /// It opens at the mouse position when the widget is right-clicked
+    /// and closes when an entry is selected, on a click outside or with Escape.
+    /// Add entries with menu_item, menu_check, menu_entry and submenus with menu_begin/menu_end
+    pub fn context_menu_begin(&mut self) {
+        let widget_id = self.last_id;
+        let id = self.generate_id(&format!("{}#context", widget_id));
+        let mouse_pos: Pos = self.mouse_pos.into();
+        let mut anchor = Rect::default();
+        if self.hover == widget_id && self.mouse_pressed & MOUSE_BUTTON_RIGHT != 0 {
+            self.menu_open = vec![id];
+            self.context_menu_pos = mouse_pos;
+            // the right click opening the menu is not an outside click
+            anchor = Rect::new(mouse_pos.x, mouse_pos.y, 1, 1);
+        } else if self.menu_open.first() == Some(&id) && self.take_special_key(SpecialKey::Escape) {
+            self.menu_open.pop();
+        }
+        self.prefix_id(&format!("{}#context", widget_id));
+        self.push_menu_level(id, self.context_menu_pos, anchor);
+    }
+    pub fn context_menu_end(&mut self) {
+        self.menu_end();
+    }
+    /// draws the title of a menu in the bar. returns its area
+    fn menu_title(&mut self, id: Id, text: &str, mnemonic: Option<char>) -> Rect {
+        let r = Rect::new(
+            self.menubar_x,
+            self.menubar.y,
+            text.chars().count() as Coord + 2,
+            1,
+        );
+        self.menubar_x += r.w;
+        self.update_control(id, &r, false);
+        let hover = self.hover == id;
+        let open = self.menu_open.first() == Some(&id);
+        let alt_mnemonic = self.key_modifiers & KEY_MOD_ALT != 0
+            && matches!(mnemonic, Some(m) if self.take_char_key(m));
+        if (hover && self.mouse_pressed & MOUSE_BUTTON_LEFT != 0) || self.key_activated(id) {
+            if open {
+                self.menu_open.clear();
+            } else {
+                self.menu_open = vec![id];
+            }
+        } else if alt_mnemonic || (hover && !open && !self.menu_open.is_empty()) {
+            // switch to another menu by hovering its title
+            self.menu_open = vec![id];
+        }
+        let open = self.menu_open.first() == Some(&id);
+        let (background_code, foreground_code) = if hover || open {
+            (ColorCode::ButtonBackgroundHover, ColorCode::ButtonTextHover)
+        } else if self.focus == id {
+            (ColorCode::ButtonBackgroundFocus, ColorCode::ButtonTextFocus)
+        } else {
+            (ColorCode::ButtonBackground, ColorCode::ButtonText)
+        };
+        let back = self.get_color(background_code);
+        let fore = self.get_color(foreground_code);
+        self.draw_rect(r, back);
+        self.draw_text(r, text, TextAlign::Center, fore);
+        r
+    }
+    fn push_menu_level(&mut self, id: Id, pos: Pos, anchor: Rect) {
+        let open = self.menu_open.get(self.menu_stack.len()) == Some(&id);
+        let command_start = self.commands.len();
+        let areas_start = self.layer_areas.len();
+        self.menu_stack.push(MenuLevel {
+            id,
+            pos,
+            open,
+            entries: Vec::new(),
+            command_start,
+            anchor,
+            areas_start,
+        });
+    }
+    /// adds an entry to the current menu.
+    /// returns its area and whether it was clicked or its mnemonic was pressed
+    fn add_menu_entry(&mut self, entry: MenuEntry, mnemonic: Option<char>) -> (Rect, bool) {
+        let depth = self.menu_stack.len() - 1;
+        let level = self
+            .menu_stack
+            .last_mut()
+            .expect("menu entries must be added between menu_begin and menu_end");
+        let index = level.entries.len() as Coord;
+        let enabled = entry.enabled;
+        level.entries.push(entry);
+        let (open, pos, menu_id) = (level.open, level.pos, level.id);
+        // the panel width is known at the end of the previous frame
+        let width = self.menu_width.get(&menu_id).copied().unwrap_or(0);
+        let r = Rect::new(pos.x, pos.y + index, width, 1);
+        if !open {
+            return (r, false);
+        }
+        let mouse_pos: Pos = self.mouse_pos.into();
+        let hover = r.contains(mouse_pos);
+        if hover {
+            // close the submenus of the other entries
+            self.menu_open.truncate(depth + 1);
+        }
+        if !enabled {
+            return (r, false);
+        }
+        let deepest = self.menu_open.len() == depth + 1;
+        let activated = (hover && self.mouse_pressed & MOUSE_BUTTON_LEFT != 0)
+            || (deepest
+                && self.key_modifiers & KEY_MOD_CTRL == 0
+                && matches!(mnemonic, Some(m) if self.take_char_key(m)));
+        (r, activated)
+    }
+    fn draw_menu_panel(&mut self, level: &MenuLevel, panel: Rect) {
+        let mouse_pos: Pos = self.mouse_pos.into();
+        let back = self.get_color(ColorCode::ButtonBackground);
+        let back_highlight = self.get_color(ColorCode::ButtonBackgroundHover);
+        let fore = self.get_color(ColorCode::ButtonText);
+        let fore_highlight = self.get_color(ColorCode::ButtonTextHover);
+        let fore_disabled = self.get_color(ColorCode::ButtonTextDisabled);
+        self.draw_rect(panel, back);
+        for (i, entry) in level.entries.iter().enumerate() {
+            let r = Rect::new(panel.x, panel.y + i as Coord, panel.w, 1);
+            let highlight = entry.enabled
+                && (r.contains(mouse_pos)
+                    || (entry.submenu != NULL_ID && self.menu_open.contains(&entry.submenu)));
+            let fore = if !entry.enabled {
+                fore_disabled
+            } else if highlight {
+                self.draw_rect(r, back_highlight);
+                fore_highlight
+            } else {
+                fore
+            };
+            if let Some(checked) = entry.checked {
+                self.push_command(Command::CheckBox(Pos { x: r.x, y: r.y }, checked, fore));
+            }
+            let label_rect = Rect::new(r.x + 2, r.y, r.w - 2, 1);
+            self.draw_text(label_rect, &entry.label, TextAlign::Left, fore);
+            if entry.submenu != NULL_ID {
+                let arrow = Pos {
+                    x: r.x + r.w - 1,
+                    y: r.y,
+                };
+                self.push_command(Command::DropDown(arrow, false, fore));
+            } else if !entry.shortcut.is_empty() {
+                let shortcut_rect = Rect::new(r.x, r.y, r.w - 2, 1);
+                self.draw_text(shortcut_rect, &entry.shortcut, TextAlign::Right, fore);
+            }
+        }
+    }
+    fn take_char_key(&mut self, c: char) -> bool {
+        if self.input_blocked() {
+            return false;
+        }
+        if let Some(i) = self.char_keys.iter().position(|k| *k == c) {
+            self.char_keys.remove(i);
+            return true;
+        }
+        false
+    }
+    /// checks if the keys of a shortcut like "Ctrl+Shift+S" were pressed this frame
+    fn take_accelerator(&mut self, shortcut: &str) -> bool {
+        if self.input_blocked() {
+            return false;
+        }
+        let parts: Vec<&str> = shortcut.split('+').collect();
+        let (key, modifier_names) = parts.split_last().unwrap();
+        let mut modifiers = 0;
+        for name in modifier_names {
+            modifiers |= match name.to_lowercase().as_str() {
+                "ctrl" => KEY_MOD_CTRL,
+                "shift" => KEY_MOD_SHIFT,
+                "alt" => KEY_MOD_ALT,
+                _ => return false,
+            };
+        }
+        if modifiers != self.key_modifiers {
+            return false;
+        }
+        if let Some(special_key) = special_key_from_name(key) {
+            return self.take_special_key(special_key);
+        }
+        let mut chars = key.chars();
+        match (chars.next(), chars.next()) {
+            (Some(c), None) => self.take_char_key(c.to_ascii_lowercase()),
+            _ => false,
+        }
+    }
+}
+
+/// removes the & mnemonic marker from a label. && is a literal &
+fn parse_mnemonic(label: &str) -> (String, Option<char>) {
+    let mut text = String::new();
+    let mut mnemonic = None;
+    let mut chars = label.chars();
+    while let Some(c) = chars.next() {
+        if c != '&' {
+            text.push(c);
+            continue;
+        }
+        match chars.next() {
+            Some('&') => text.push('&'),
+            Some(m) => {
+                mnemonic = mnemonic.or_else(|| Some(m.to_ascii_lowercase()));
+                text.push(m);
+            }
+            None => (),
+        }
+    }
+    (text, mnemonic)
+}
+
+fn special_key_from_name(name: &str) -> Option<SpecialKey> {
+    Some(match name.to_lowercase().as_str() {
+        "backspace" => SpecialKey::Backspace,
+        "del" | "delete" => SpecialKey::Delete,
+        "left" => SpecialKey::Left,
+        "right" => SpecialKey::Right,
+        "up" => SpecialKey::Up,
+        "down" => SpecialKey::Down,
+        "home" => SpecialKey::Home,
+        "end" => SpecialKey::End,
+        "pageup" => SpecialKey::PageUp,
+        "pagedown" => SpecialKey::PageDown,
+        "tab" => SpecialKey::Tab,
+        "enter" => SpecialKey::Enter,
+        "esc" | "escape" => SpecialKey::Escape,
+        "space" => SpecialKey::Space,
+        _ => return None,
+    })
+}
@@ -1,4 +1,63 @@
-use crate::{ColorCode, Context, Coord, Rect, TextAlign, MOUSE_BUTTON_LEFT};
+use crate::{
+    ColorCode, Context, Coord, Id, Rect, SpecialKey, SpinnerValue, TextAlign, MOUSE_BUTTON_LEFT,
+};
+
+/// PageUp/PageDown move a slider by 1/SLIDER_PAGES of its range
+const SLIDER_PAGES: f32 = 10.0;
+
+/// geometry of a slider
+struct SliderTrack {
+    r: Rect,
+    vertical: bool,
+    min: f32,
+    max: f32,
+}
+
+impl SliderTrack {
+    fn len(&self) -> Coord {
+        if self.vertical {
+            self.r.h
+        } else {
+            self.r.w
+        }
+    }
+    fn value_per_cell(&self) -> f32 {
+        (self.max - self.min) / (self.len() - 1).max(1) as f32
+    }
+    /// cell of the track showing this value, 0 being the min value
+    fn cell(&self, value: f32) -> Coord {
+        let coef = (value - self.min) / (self.max - self.min);
+        ((coef * (self.len() - 1) as f32).round() as Coord)
+            .max(0)
+            .min(self.len() - 1)
+    }
+    fn value(&self, cell: Coord) -> f32 {
+        self.min + cell as f32 * self.value_per_cell()
+    }
+    fn cell_rect(&self, cell: Coord) -> Rect {
+        if self.vertical {
+            Rect::new(self.r.x, self.r.y + self.r.h - 1 - cell, 1, 1)
+        } else {
+            Rect::new(self.r.x + cell, self.r.y, 1, 1)
+        }
+    }
+    /// position of the mouse along the track, in cells. Not clamped
+    fn mouse_cell(&self, (x, y): (f32, f32)) -> Coord {
+        if self.vertical {
+            self.r.y + self.r.h - 1 - y as Coord
+        } else {
+            x as Coord - self.r.x
+        }
+    }
+    /// rounds the value to the step and keeps it inside the range
+    fn snap(&self, value: f32, step: Option<f32>) -> f32 {
+        let value = match step {
+            Some(step) => self.min + ((value - self.min) / step).round() * step,
+            None => value,
+        };
+        value.max(self.min).min(self.max)
+    }
+}
 
 impl Context {
     // =======================================================
@@ -6,41 +65,28 @@ impl Context {
     // Sliders
     //
     // =======================================================
+    /// the next slider or range slider values are multiples of step from their min value
+    pub fn step(&mut self, step: f32) -> &mut Self {
+        assert!(step > 0.0);
+        self.next_step = Some(step);
+        self
+    }
+    /// the next horizontal slider or range slider displays its value over the track
+    pub fn show_value(&mut self) -> &mut Self {
+        self.next_show_value = true;
+        self
+    }
+    /// a horizontal slider. Drag the handle or click the track to change the value.
+    /// When focused, Left/Right move by one step (or one cell) and PageUp/PageDown by a tenth of the range.
     pub fn fslider(
         &mut self,
-        id1: &str,
+        id: &str,
         width: Coord,
         min_val: f32,
         max_val: f32,
         start_val: f32,
     ) -> f32 {
-        assert!(min_val < max_val);
-        assert!(start_val >= min_val && start_val <= max_val);
-        self.try_commit();
-        let id = self.generate_id(id1);
-        let value = *self.slider_state.entry(id).or_insert(start_val);
-        let r = self.next_rectangle(width, 1);
-        let was_focus = self.focus == id;
-        self.update_control(id, &r, true);
-        let focus = self.focus == id;
-        let hover = self.hover == id;
-        let pressed = focus && self.mouse_down == MOUSE_BUTTON_LEFT;
-        if pressed {
-            if !self.dnd_on {
-                self.start_dnd(value);
-            } else {
-                let delta = self.mouse_pos.0 - self.dnd_start.0;
-                let value_delta = delta as f32 * (max_val - min_val) / width as f32;
-                let new_value = (self.dnd_value + value_delta).max(min_val).min(max_val);
-                self.slider_state.insert(id, new_value);
-            }
-        } else if was_focus {
-            self.dnd_on = false;
-        }
-        let coef = (value - min_val) / (max_val - min_val);
-        let handle_pos = r.x + ((r.w as f32 * coef + 0.5) as Coord).min(r.w - 1);
-        self.draw_slider(r, handle_pos, focus || hover);
-        value
+        self.slider(id, width, false, min_val, max_val, start_val)
     }
 
     pub fn islider(
@@ -51,53 +97,286 @@ impl Context {
         max_val: i32,
         start_val: i32,
     ) -> i32 {
+        self.slider(id, width, false, min_val, max_val, start_val)
+    }
+
+    /// a vertical slider with the max value at the top. Up/Down move by one step when focused
+    pub fn fslider_vertical(
+        &mut self,
+        id: &str,
+        height: Coord,
+        min_val: f32,
+        max_val: f32,
+        start_val: f32,
+    ) -> f32 {
+        self.slider(id, height, true, min_val, max_val, start_val)
+    }
+
+    pub fn islider_vertical(
+        &mut self,
+        id: &str,
+        height: Coord,
+        min_val: i32,
+        max_val: i32,
+        start_val: i32,
+    ) -> i32 {
+        self.slider(id, height, true, min_val, max_val, start_val)
+    }
+
+    fn slider<T: SpinnerValue>(
+        &mut self,
+        id: &str,
+        len: Coord,
+        vertical: bool,
+        min_val: T,
+        max_val: T,
+        start_val: T,
+    ) -> T {
         assert!(min_val < max_val);
         assert!(start_val >= min_val && start_val <= max_val);
         self.try_commit();
         let id = self.generate_id(id);
-        let value = *self.button_state.entry(id).or_insert(start_val);
-        let r = self.next_rectangle(width, 1);
+        let (min, max) = (min_val.to_f32(), max_val.to_f32());
+        let value = *self.slider_state.entry(id).or_insert(start_val.to_f32());
+        let step = self.next_step.take();
+        let show_value = std::mem::take(&mut self.next_show_value);
+        let r = if vertical {
+            self.next_rectangle(1, len)
+        } else {
+            self.next_rectangle(len, 1)
+        };
+        let track = SliderTrack {
+            r,
+            vertical,
+            min,
+            max,
+        };
         let was_focus = self.focus == id;
         self.update_control(id, &r, true);
         let focus = self.focus == id;
         let hover = self.hover == id;
-        let pressed = focus && self.mouse_down == MOUSE_BUTTON_LEFT;
-        if pressed {
-            if !self.dnd_on {
-                self.start_dnd(value as f32);
+        let value = self.update_slider_handle(id, &track, value, hover, was_focus);
+        let value = if focus {
+            self.update_slider_keys(&track, value, step, T::min_step())
+        } else {
+            value
+        };
+        let value = T::from_f32(track.snap(value, step)).to_f32();
+        self.slider_state.insert(id, value);
+        self.draw_slider(&track, None, track.cell(value), focus || hover);
+        if show_value && !vertical {
+            let label = T::from_f32(value).format(T::from_f32(step.unwrap_or(0.01)));
+            let fore = self.get_color(ColorCode::Text);
+            self.draw_text(r, &label, TextAlign::Center, fore);
+        }
+        T::from_f32(value)
+    }
+
+    /// drag'n drop of a handle. Clicking the track away from the handle moves it under the mouse
+    fn update_slider_handle(
+        &mut self,
+        id: Id,
+        track: &SliderTrack,
+        value: f32,
+        hover: bool,
+        was_focus: bool,
+    ) -> f32 {
+        let mouse_cell = track.mouse_cell(self.mouse_pos);
+        if hover && self.mouse_pressed & MOUSE_BUTTON_LEFT != 0 {
+            let value = if mouse_cell == track.cell(value) {
+                value
             } else {
-                let delta = self.mouse_pos.0 - self.dnd_start.0;
-                let value_delta = delta as f32 * (max_val - min_val) as f32 / width as f32;
-                let new_value = ((self.dnd_value + value_delta) as i32)
-                    .max(min_val)
-                    .min(max_val);
-                self.button_state.insert(id, new_value);
+                track.value(mouse_cell)
+            };
+            // the drag always starts from the click position
+            self.start_dnd(value);
+            value
+        } else if self.focus == id && self.dnd_on && self.mouse_down & MOUSE_BUTTON_LEFT != 0 {
+            let delta = mouse_cell - track.mouse_cell(self.dnd_start);
+            self.dnd_value + delta as f32 * track.value_per_cell()
+        } else {
+            if was_focus && self.mouse_down & MOUSE_BUTTON_LEFT == 0 {
+                self.dnd_on = false;
             }
-        } else if was_focus {
-            self.dnd_on = false;
+            value
+        }
+    }
+
+    /// the keys move the value by at least min_step, so that integer values always change
+    fn update_slider_keys(
+        &mut self,
+        track: &SliderTrack,
+        value: f32,
+        step: Option<f32>,
+        min_step: f32,
+    ) -> f32 {
+        let (dec, inc) = if track.vertical {
+            (SpecialKey::Down, SpecialKey::Up)
+        } else {
+            (SpecialKey::Left, SpecialKey::Right)
+        };
+        let key_step = step.unwrap_or_else(|| track.value_per_cell()).max(min_step);
+        let page = ((track.max - track.min) / SLIDER_PAGES).max(min_step);
+        let mut value = value;
+        if self.take_special_key(inc) {
+            value += key_step;
+        }
+        if self.take_special_key(dec) {
+            value -= key_step;
+        }
+        if self.take_special_key(SpecialKey::PageUp) {
+            value += page;
+        }
+        if self.take_special_key(SpecialKey::PageDown) {
+            value -= page;
         }
-        let coef = (value - min_val) as f32 / (max_val - min_val) as f32;
-        let handle_pos = r.x + ((r.w as f32 * coef + 0.5) as Coord).min(r.w - 1);
-        self.draw_slider(r, handle_pos, focus || hover);
         value
     }
 
-    fn draw_slider(&mut self, r: Rect, handle_pos: Coord, active: bool) {
+    /// draws the track and the handle. With two handles, the span between them is highlighted
+    fn draw_slider(
+        &mut self,
+        track: &SliderTrack,
+        low: Option<Coord>,
+        handle: Coord,
+        active: bool,
+    ) {
+        let r = track.r;
         let back = self.get_color(if active {
             ColorCode::ButtonBackgroundHover
         } else {
             ColorCode::ButtonBackground
         });
         self.draw_rect(r, back);
+        if let Some(low) = low {
+            let span_col = self.get_color(ColorCode::ProgressBack);
+            let (first, last) = (track.cell_rect(low), track.cell_rect(handle));
+            let span = if track.vertical {
+                Rect::new(r.x, last.y, 1, first.y - last.y + 1)
+            } else {
+                Rect::new(first.x, r.y, last.x - first.x + 1, 1)
+            };
+            self.draw_rect(span, span_col);
+        }
         let fore = self.get_color(ColorCode::Text);
         self.draw_line(r.x, r.y, r.x + r.w, r.y + r.h, fore);
-        let handle_area = Rect {
-            x: handle_pos,
-            y: r.y,
-            w: 1,
-            h: 1,
+        let handle_col = self.get_color(ColorCode::ProgressFore);
+        if let Some(low) = low {
+            self.draw_rect(track.cell_rect(low), handle_col);
+        }
+        self.draw_rect(track.cell_rect(handle), handle_col);
+    }
+    // =======================================================
+    //
+    // Range slider
+    //
+    // =======================================================
+    /// a horizontal slider with two handles selecting a (low, high) range.
+    /// The handles can't cross each other. A click on the track moves the closest handle.
+    /// The keyboard moves the last handle grabbed with the mouse.
+    pub fn frange_slider(
+        &mut self,
+        id: &str,
+        width: Coord,
+        min_val: f32,
+        max_val: f32,
+        start_val: (f32, f32),
+    ) -> (f32, f32) {
+        self.range_slider(id, width, min_val, max_val, start_val)
+    }
+
+    pub fn irange_slider(
+        &mut self,
+        id: &str,
+        width: Coord,
+        min_val: i32,
+        max_val: i32,
+        start_val: (i32, i32),
+    ) -> (i32, i32) {
+        self.range_slider(id, width, min_val, max_val, start_val)
+    }
+
+    fn range_slider<T: SpinnerValue>(
+        &mut self,
+        id: &str,
+        width: Coord,
+        min_val: T,
+        max_val: T,
+        (start_low, start_high): (T, T),
+    ) -> (T, T) {
+        assert!(min_val < max_val);
+        assert!(start_low >= min_val && start_low <= start_high && start_high <= max_val);
+        self.try_commit();
+        self.prefix_id(id);
+        let low_id = self.generate_id("low");
+        let high_id = self.generate_id("high");
+        self.id_prefix.pop();
+        let id = self.generate_id(id);
+        let mut low = *self
+            .slider_state
+            .entry(low_id)
+            .or_insert(start_low.to_f32());
+        let mut high = *self
+            .slider_state
+            .entry(high_id)
+            .or_insert(start_high.to_f32());
+        let step = self.next_step.take();
+        let show_value = std::mem::take(&mut self.next_show_value);
+        let r = self.next_rectangle(width, 1);
+        let track = SliderTrack {
+            r,
+            vertical: false,
+            min: min_val.to_f32(),
+            max: max_val.to_f32(),
         };
-        self.draw_text(handle_area, "|", TextAlign::Left, fore);
+        let was_focus = self.focus == id;
+        self.update_control(id, &r, true);
+        let focus = self.focus == id;
+        let hover = self.hover == id;
+        if hover && self.mouse_pressed & MOUSE_BUTTON_LEFT != 0 {
+            let cell = track.mouse_cell(self.mouse_pos);
+            let (low_cell, high_cell) = (track.cell(low), track.cell(high));
+            let grab_high = if low_cell == high_cell {
+                // stacked handles : grab the one that can move toward the mouse
+                cell > high_cell || (cell == high_cell && high_cell < track.len() - 1)
+            } else {
+                cell - low_cell > high_cell - cell
+            };
+            self.range_slider_high.insert(id, grab_high);
+        }
+        let grab_high = self.range_slider_high.get(&id).copied().unwrap_or(false);
+        let value = if grab_high { high } else { low };
+        let value = self.update_slider_handle(id, &track, value, hover, was_focus);
+        let value = if focus {
+            self.update_slider_keys(&track, value, step, T::min_step())
+        } else {
+            value
+        };
+        let value = T::from_f32(track.snap(value, step)).to_f32();
+        if grab_high {
+            high = value.max(low);
+        } else {
+            low = value.min(high);
+        }
+        self.slider_state.insert(low_id, low);
+        self.slider_state.insert(high_id, high);
+        self.draw_slider(
+            &track,
+            Some(track.cell(low)),
+            track.cell(high),
+            focus || hover,
+        );
+        if show_value {
+            let step = T::from_f32(step.unwrap_or(0.01));
+            let label = format!(
+                "{} - {}",
+                T::from_f32(low).format(step),
+                T::from_f32(high).format(step)
+            );
+            let fore = self.get_color(ColorCode::Text);
+            self.draw_text(r, &label, TextAlign::Center, fore);
+        }
+        (T::from_f32(low), T::from_f32(high))
     }
     // =======================================================
     //
@@ -0,0 +1,191 @@
+use std::str::FromStr;
+
+use crate::{
+    ColorCode, Context, Coord, Id, Rect, SpecialKey, TextAlign, TextBoxState, MOUSE_BUTTON_LEFT,
+    NULL_ID,
+};
+
+/// number of steps for PageUp/PageDown
+const SPINNER_PAGE: f32 = 10.0;
+
+/// a numeric type that can be edited with a spinner
+pub trait SpinnerValue: Copy + PartialOrd + FromStr {
+    fn to_f32(self) -> f32;
+    fn from_f32(value: f32) -> Self;
+    /// text displayed in the spinner
+    fn format(self, step: Self) -> String;
+    /// smallest change of the value, 0.0 if it's continuous
+    fn min_step() -> f32 {
+        0.0
+    }
+}
+
+impl SpinnerValue for i32 {
+    fn to_f32(self) -> f32 {
+        self as f32
+    }
+    fn from_f32(value: f32) -> Self {
+        value.round() as i32
+    }
+    fn format(self, _step: Self) -> String {
+        self.to_string()
+    }
+    fn min_step() -> f32 {
+        1.0
+    }
+}
+
+impl SpinnerValue for f32 {
+    fn to_f32(self) -> f32 {
+        self
+    }
+    fn from_f32(value: f32) -> Self {
+        value
+    }
+    /// displays as many decimals as the step has
+    fn format(self, step: Self) -> String {
+        let step = step.to_string();
+        let decimals = step.find('.').map_or(0, |i| step.len() - i - 1);
+        format!("{:.*}", decimals, self)
+    }
+}
+
+impl Context {
+    // =======================================================
+    //
+    // Spinner
+    //
+    // =======================================================
+    /// a numeric field displayed as `- [ 42 ] +`. width is the size of the number field.
+    /// The value can be changed :
+    /// * by clicking the - and + signs,
+    /// * by dragging the mouse horizontally over the number,
+    /// * with the arrow keys, PageUp/PageDown or the mouse wheel,
+    /// * by clicking the number (or pressing Enter) and typing a new value.
+    ///
+    /// The value is always a multiple of step from min_val.
+    /// returns (value, value_has_changed_this_frame)
+    pub fn spinner<T: SpinnerValue>(
+        &mut self,
+        id: &str,
+        width: Coord,
+        min_val: T,
+        max_val: T,
+        step: T,
+        start_val: T,
+    ) -> (T, bool) {
+        assert!(min_val < max_val);
+        assert!(start_val >= min_val && start_val <= max_val);
+        self.try_commit();
+        let id = self.generate_id(id);
+        let (min, max, fstep) = (min_val.to_f32(), max_val.to_f32(), step.to_f32());
+        let old_value = *self.slider_state.entry(id).or_insert(start_val.to_f32());
+        let r = self.next_rectangle(width + 6, 1);
+        let field = Rect::new(r.x + 3, r.y, width, 1);
+        let was_focus = self.focus == id;
+        self.update_control(id, &r, true);
+        let focus = self.focus == id;
+        let hover = self.hover == id;
+        let mouse_x = self.mouse_pos.0 as Coord;
+        let mut value = old_value;
+        if self.spinner_edit == id {
+            if !focus || self.take_special_key(SpecialKey::Enter) {
+                let text = &self.textbox_state[&id].value;
+                if let Ok(typed) = text.trim().parse::<T>() {
+                    value = typed.to_f32();
+                }
+                self.stop_spinner_edit();
+            } else if self.take_special_key(SpecialKey::Escape) {
+                self.stop_spinner_edit();
+            }
+        } else if focus {
+            if hover && self.mouse_pressed & MOUSE_BUTTON_LEFT != 0 {
+                if mouse_x == r.x {
+                    value -= fstep;
+                } else if mouse_x == r.x + r.w - 1 {
+                    value += fstep;
+                } else if mouse_x >= field.x && mouse_x < field.x + field.w {
+                    self.start_dnd(value);
+                }
+            } else if self.dnd_on && self.mouse_down & MOUSE_BUTTON_LEFT != 0 {
+                let delta = (self.mouse_pos.0 - self.dnd_start.0) as Coord;
+                value = self.dnd_value + delta as f32 * fstep;
+            } else if self.dnd_on {
+                self.dnd_on = false;
+                if self.mouse_pos.0 as Coord == self.dnd_start.0 as Coord {
+                    // a click without drag edits the number
+                    self.start_spinner_edit(id, T::from_f32(value).format(step));
+                }
+            }
+            if self.take_special_key(SpecialKey::Up) || self.take_special_key(SpecialKey::Right) {
+                value += fstep;
+            }
+            if self.take_special_key(SpecialKey::Down) || self.take_special_key(SpecialKey::Left) {
+                value -= fstep;
+            }
+            if self.take_special_key(SpecialKey::PageUp) {
+                value += fstep * SPINNER_PAGE;
+            }
+            if self.take_special_key(SpecialKey::PageDown) {
+                value -= fstep * SPINNER_PAGE;
+            }
+            if self.take_special_key(SpecialKey::Enter) {
+                self.start_spinner_edit(id, T::from_f32(value).format(step));
+            } else if self.spinner_edit != id
+                && self
+                    .text_input
+                    .chars()
+                    .any(|c| c.is_ascii_digit() || c == '-' || c == '.')
+            {
+                // typing a number replaces the value
+                self.start_spinner_edit(id, T::from_f32(value).format(step));
+            }
+        } else if was_focus {
+            self.dnd_on = false;
+        }
+        if hover && self.mouse_wheel != 0 && self.spinner_edit != id {
+            value += self.mouse_wheel as f32 * fstep;
+            self.mouse_wheel = 0;
+        }
+        // snap to the step and clamp
+        let value = (min + ((value - min) / fstep).round() * fstep)
+            .max(min)
+            .min(max);
+        let value = T::from_f32(value).to_f32();
+        self.slider_state.insert(id, value);
+        let back = self.get_color(if focus || hover {
+            ColorCode::ButtonBackgroundHover
+        } else {
+            ColorCode::ButtonBackground
+        });
+        let fore = self.get_color(ColorCode::Text);
+        self.draw_rect(r, back);
+        self.draw_text(r, "- [", TextAlign::Left, fore);
+        self.draw_text(r, "] +", TextAlign::Right, fore);
+        if self.spinner_edit == id {
+            self.textbox_field(id, field, focus, hover, None, None);
+        } else {
+            let text = T::from_f32(value).format(step);
+            self.draw_text(field, &text, TextAlign::Center, fore);
+        }
+        (T::from_f32(value), value != old_value)
+    }
+    /// switch a spinner to text edition with its whole value selected
+    fn start_spinner_edit(&mut self, id: Id, text: String) {
+        let len = text.chars().count();
+        self.textbox_state.insert(
+            id,
+            TextBoxState {
+                value: text,
+                cursor_pos: len,
+                selection_anchor: Some(0),
+                ..Default::default()
+            },
+        );
+        self.spinner_edit = id;
+    }
+    fn stop_spinner_edit(&mut self) {
+        self.textbox_state.remove(&self.spinner_edit);
+        self.spinner_edit = NULL_ID;
+    }
+}
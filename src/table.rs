@@ -0,0 +1,345 @@
+use std::ops::Range;
+
+use crate::{
+    ColorCode, Context, Coord, DeferedCommand, Id, LayoutMode, Rect, RowSelection, SpecialKey,
+    TextAlign, KEY_MOD_CTRL, KEY_MOD_SHIFT, MOUSE_BUTTON_LEFT,
+};
+
+/// number of rows scrolled by a mouse wheel notch
+const TABLE_WHEEL_STEP: usize = 3;
+
+/// a column of a table
+#[derive(Clone, Debug)]
+pub struct Column {
+    pub label: String,
+    /// initial width. The player can resize the columns by dragging the header separators
+    pub width: Coord,
+}
+
+impl Column {
+    pub fn new(label: &str, width: Coord) -> Self {
+        Self {
+            label: label.to_owned(),
+            width,
+        }
+    }
+}
+
+/// sort order requested by a click on a table header
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct TableSort {
+    pub column: usize,
+    pub ascending: bool,
+}
+
+impl RowSelection {
+    /// click on a row. extend selects the range from the last clicked row,
+    /// toggle adds/removes the row from the selection
+    pub(crate) fn select(&mut self, row: usize, extend: bool, toggle: bool) {
+        if extend {
+            let anchor = self.anchor.min(self.row_count.saturating_sub(1));
+            let (start, end) = (anchor.min(row), anchor.max(row));
+            self.selection = (start..=end).collect();
+        } else if toggle {
+            if !self.selection.remove(&row) {
+                self.selection.insert(row);
+            }
+            self.anchor = row;
+        } else {
+            self.selection.clear();
+            self.selection.insert(row);
+            self.anchor = row;
+        }
+        self.cursor = row;
+    }
+    /// keep a row visible in a view of page rows
+    pub(crate) fn scroll_to(&mut self, row: usize, page: usize) {
+        if row < self.offset {
+            self.offset = row;
+        } else if row >= self.offset + page {
+            self.offset = row + 1 - page;
+        }
+    }
+    pub(crate) fn sorted_selection(&self) -> Vec<usize> {
+        let mut selection: Vec<usize> = self.selection.iter().copied().collect();
+        selection.sort_unstable();
+        selection
+    }
+}
+
+impl Context {
+    // =======================================================
+    //
+    // Table
+    //
+    // =======================================================
+    /// starts a table with a header row. Returns the range of visible rows.
+    /// Only these rows should be built : call table_row for each of them,
+    /// followed by one widget per column. table_end returns the new sort order
+    /// when a column title is clicked.
+    /// A click selects a row, ctrl+click toggles it, shift+click selects a range.
+    /// When the table has the focus, Up/Down/PageUp/PageDown/Home/End move the selection
+    /// (extended with Shift) and Space toggles the current row.
+    /// ctx.last_id() returns the table id right after this call.
+    pub fn table_begin(
+        &mut self,
+        id: &str,
+        columns: &[Column],
+        height: Coord,
+        row_count: usize,
+    ) -> Range<usize> {
+        self.try_commit();
+        let table_id = self.generate_id(id);
+        let state = self.table_state.entry(table_id).or_default();
+        if state.widths.len() != columns.len() {
+            state.widths = columns.iter().map(|c| c.width).collect();
+        }
+        state.rows.row_count = row_count;
+        let width = state.widths.iter().sum::<Coord>() + state.widths.len() as Coord;
+        let r = self.next_rectangle(width, height);
+        let header = Rect::new(r.x, r.y, r.w - 1, 1);
+        let body = Rect::new(r.x, r.y + 1, r.w - 1, r.h - 1);
+        self.update_control(table_id, &r, false);
+        let focus = self.focus == table_id;
+        let hover = self.hover == table_id;
+        self.update_table_header(table_id, header, hover, focus);
+        let mut rows = std::mem::take(&mut self.table_state.get_mut(&table_id).unwrap().rows);
+        self.update_row_selection(&mut rows, body, hover, focus, true);
+        self.table_state.get_mut(&table_id).unwrap().rows = rows;
+        self.prefix_id(id);
+        let offset = self.table_state[&table_id].rows.offset as Coord;
+        let offset = self.scrollbar(
+            "scrollbar",
+            Rect::new(r.x + r.w - 1, body.y, 1, body.h),
+            true,
+            offset,
+            row_count as Coord,
+            body.h,
+        ) as usize;
+        let state = self.table_state.get_mut(&table_id).unwrap();
+        state.rows.offset = offset;
+        state.body = body;
+        let widths = state.widths.clone();
+        self.draw_table_header(table_id, columns, header);
+        let back = self.get_color(ColorCode::Background);
+        self.draw_rect(body, back);
+        self.table_stack.push(table_id);
+        self.new_layout(LayoutMode::Grid)
+            .flexgrid(&widths)
+            .hpadding(1)
+            .fixed_pos(body.x, body.y, body.w, body.h)
+            .defered(DeferedCommand::Clip);
+        self.last_id = table_id;
+        offset..(offset + body.h.max(0) as usize).min(row_count)
+    }
+    /// starts a new row. Returns true if it is selected
+    pub fn table_row(&mut self, row: usize) -> bool {
+        let table_id = *self
+            .table_stack
+            .last()
+            .expect("table_row must be called inside table_begin/table_end");
+        self.try_commit();
+        let state = self.table_state.get_mut(&table_id).unwrap();
+        let selected = state.rows.selection.contains(&row);
+        let current = row == state.rows.cursor;
+        let body = state.body;
+        if state.row_color_pushed {
+            state.row_color_pushed = false;
+            self.pop_color(ColorCode::Background);
+        }
+        let row_rect = Rect::new(
+            body.x,
+            body.y + row as Coord - self.table_state[&table_id].rows.offset as Coord,
+            body.w,
+            1,
+        );
+        let focus = self.focus == table_id;
+        let back = if selected {
+            Some(self.get_color(ColorCode::TextSelection))
+        } else if current && focus {
+            Some(self.get_color(ColorCode::ButtonBackgroundFocus))
+        } else {
+            None
+        };
+        if let Some(back) = back {
+            // the cells are drawn with the row background
+            self.draw_rect(row_rect, back);
+            self.push_color(ColorCode::Background, back);
+            self.table_state
+                .get_mut(&table_id)
+                .unwrap()
+                .row_color_pushed = true;
+        }
+        selected
+    }
+    /// ends the table. Returns the new sort order if a header was clicked this frame
+    pub fn table_end(&mut self) -> Option<TableSort> {
+        let table_id = self
+            .table_stack
+            .pop()
+            .expect("unmatched table_begin/table_end calls");
+        self.end_container();
+        let state = self.table_state.get_mut(&table_id).unwrap();
+        if state.row_color_pushed {
+            state.row_color_pushed = false;
+            self.pop_color(ColorCode::Background);
+        }
+        let state = self.table_state.get_mut(&table_id).unwrap();
+        if std::mem::take(&mut state.sort_changed) {
+            state.sort
+        } else {
+            None
+        }
+    }
+    /// the selected rows of a table, in increasing order
+    pub fn table_selection(&self, table_id: Id) -> Vec<usize> {
+        self.table_state
+            .get(&table_id)
+            .map_or(Vec::new(), |state| state.rows.sorted_selection())
+    }
+    /// the current sort order of a table
+    pub fn table_sort(&self, table_id: Id) -> Option<TableSort> {
+        self.table_state.get(&table_id).and_then(|state| state.sort)
+    }
+    /// clicks on the column titles change the sort order.
+    /// Dragging a separator resizes the column on its left
+    fn update_table_header(&mut self, table_id: Id, header: Rect, hover: bool, focus: bool) {
+        let mouse_x = self.mouse_pos.0 as Coord;
+        let mouse_y = self.mouse_pos.1 as Coord;
+        let pressed = hover && self.mouse_pressed & MOUSE_BUTTON_LEFT != 0 && mouse_y == header.y;
+        let mut dnd = None;
+        let state = self.table_state.get_mut(&table_id).unwrap();
+        if let Some(column) = state.resizing {
+            if focus && self.mouse_down & MOUSE_BUTTON_LEFT != 0 && self.dnd_on {
+                let delta = mouse_x - self.dnd_start.0 as Coord;
+                state.widths[column] = (self.dnd_value as Coord + delta).max(1);
+            } else {
+                state.resizing = None;
+                self.dnd_on = false;
+            }
+        } else if pressed {
+            let mut x = header.x;
+            for (column, width) in state.widths.iter().enumerate() {
+                if mouse_x == x + width {
+                    state.resizing = Some(column);
+                    dnd = Some(*width as f32);
+                    break;
+                } else if mouse_x >= x && mouse_x < x + width {
+                    state.sort = Some(match state.sort {
+                        Some(sort) if sort.column == column => TableSort {
+                            column,
+                            ascending: !sort.ascending,
+                        },
+                        _ => TableSort {
+                            column,
+                            ascending: true,
+                        },
+                    });
+                    state.sort_changed = true;
+                    break;
+                }
+                x += width + 1;
+            }
+        }
+        if let Some(width) = dnd {
+            self.start_dnd(width);
+        }
+    }
+    /// mouse, wheel and keyboard selection of the rows of a table or a list box.
+    /// Without multi, shift, ctrl and Space are ignored and a single row is selected
+    pub(crate) fn update_row_selection(
+        &mut self,
+        rows: &mut RowSelection,
+        body: Rect,
+        hover: bool,
+        focus: bool,
+        multi: bool,
+    ) {
+        let shift = multi && self.key_modifiers & KEY_MOD_SHIFT != 0;
+        let ctrl = multi && self.key_modifiers & KEY_MOD_CTRL != 0;
+        let count = rows.row_count;
+        let page = body.h.max(1) as usize;
+        if hover && self.mouse_wheel != 0 {
+            rows.offset = if self.mouse_wheel > 0 {
+                rows.offset
+                    .saturating_sub(self.mouse_wheel as usize * TABLE_WHEEL_STEP)
+            } else {
+                rows.offset + (-self.mouse_wheel) as usize * TABLE_WHEEL_STEP
+            };
+            self.mouse_wheel = 0;
+        }
+        let mouse_y = self.mouse_pos.1 as Coord;
+        if hover
+            && self.mouse_pressed & MOUSE_BUTTON_LEFT != 0
+            && body.contains(self.mouse_pos.into())
+        {
+            let row = rows.offset + (mouse_y - body.y) as usize;
+            if row < count {
+                rows.select(row, shift, ctrl);
+            }
+        }
+        if !focus || count == 0 {
+            return;
+        }
+        let cursor = rows.cursor.min(count - 1);
+        let mut new_cursor = None;
+        if self.take_special_key(SpecialKey::Up) {
+            new_cursor = Some(cursor.saturating_sub(1));
+        }
+        if self.take_special_key(SpecialKey::Down) {
+            new_cursor = Some((cursor + 1).min(count - 1));
+        }
+        if self.take_special_key(SpecialKey::PageUp) {
+            new_cursor = Some(cursor.saturating_sub(page));
+        }
+        if self.take_special_key(SpecialKey::PageDown) {
+            new_cursor = Some((cursor + page).min(count - 1));
+        }
+        if self.take_special_key(SpecialKey::Home) {
+            new_cursor = Some(0);
+        }
+        if self.take_special_key(SpecialKey::End) {
+            new_cursor = Some(count - 1);
+        }
+        if let Some(row) = new_cursor {
+            rows.select(row, shift, false);
+            rows.scroll_to(row, page);
+        }
+        if multi && self.take_special_key(SpecialKey::Space) {
+            rows.select(cursor, false, true);
+        }
+    }
+    fn draw_table_header(&mut self, table_id: Id, columns: &[Column], header: Rect) {
+        let back = self.get_color(ColorCode::ButtonBackground);
+        let fore = self.get_color(ColorCode::ButtonText);
+        let sep = self.get_color(ColorCode::Text);
+        let state = &self.table_state[&table_id];
+        let sort = state.sort;
+        let widths = state.widths.clone();
+        self.draw_rect(Rect::new(header.x, header.y, header.w + 1, 1), back);
+        let mut x = header.x;
+        for (i, (column, width)) in columns.iter().zip(widths.iter()).enumerate() {
+            let mut title: String = column.label.chars().take(*width as usize).collect();
+            if let Some(sort) = sort.filter(|sort| sort.column == i) {
+                // sort indicator in the last cell of the title
+                title = title.chars().take(*width as usize - 1).collect();
+                let padding = (*width as usize - 1).saturating_sub(title.chars().count());
+                title += &" ".repeat(padding);
+                title.push(if sort.ascending { '^' } else { 'v' });
+            }
+            self.draw_text(
+                Rect::new(x, header.y, *width, 1),
+                &title,
+                TextAlign::Left,
+                fore,
+            );
+            self.draw_text(
+                Rect::new(x + width, header.y, 1, 1),
+                "|",
+                TextAlign::Left,
+                sep,
+            );
+            x += width + 1;
+        }
+    }
+}
@@ -0,0 +1,384 @@
+use std::io::{self, Write};
+
+use crossterm::event::{Event, KeyCode, KeyModifiers, MouseButton, MouseEvent};
+
+use crate::{
+    Color, Context, Coord, Pos, Rect, Renderer, SpecialKey, KEY_MOD_ALT, KEY_MOD_CTRL,
+    KEY_MOD_SHIFT, MOUSE_BUTTON_LEFT, MOUSE_BUTTON_MIDDLE, MOUSE_BUTTON_RIGHT,
+};
+
+const DEFAULT_FORE: Color = (255, 255, 255, 255);
+const DEFAULT_BACK: Color = (0, 0, 0, 255);
+
+/// How colors are encoded in the ANSI output
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ColorMode {
+    /// 24 bits colors : ESC[38;2;r;g;bm
+    TrueColor,
+    /// xterm 256 colors palette : ESC[38;5;nm
+    Ansi256,
+}
+
+impl ColorMode {
+    /// use truecolor if the COLORTERM environment variable advertises it
+    pub fn detect() -> Self {
+        match std::env::var("COLORTERM") {
+            Ok(ref v) if v == "truecolor" || v == "24bit" => ColorMode::TrueColor,
+            _ => ColorMode::Ansi256,
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+struct Cell {
+    ch: char,
+    fore: Color,
+    back: Color,
+}
+
+impl Default for Cell {
+    fn default() -> Self {
+        Self {
+            ch: ' ',
+            fore: DEFAULT_FORE,
+            back: DEFAULT_BACK,
+        }
+    }
+}
+
+/// A renderer drawing into a cell buffer that can be flushed to any
+/// ANSI compatible terminal.
+pub struct TerminalRenderer {
+    width: Coord,
+    height: Coord,
+    color_mode: ColorMode,
+    cells: Vec<Cell>,
+}
+
+impl TerminalRenderer {
+    pub fn new(width: Coord, height: Coord) -> Self {
+        Self {
+            width,
+            height,
+            color_mode: ColorMode::detect(),
+            cells: vec![Cell::default(); (width * height) as usize],
+        }
+    }
+    pub fn color_mode(&mut self, mode: ColorMode) -> &mut Self {
+        self.color_mode = mode;
+        self
+    }
+    pub fn resize(&mut self, width: Coord, height: Coord) {
+        self.width = width;
+        self.height = height;
+        self.cells = vec![Cell::default(); (width * height) as usize];
+    }
+    pub fn clear(&mut self, back: Color) {
+        for cell in self.cells.iter_mut() {
+            *cell = Cell {
+                back,
+                ..Default::default()
+            };
+        }
+    }
+    /// the character at a given position in the buffer
+    pub fn char_at(&self, x: Coord, y: Coord) -> Option<char> {
+        self.cell(x, y).map(|c| c.ch)
+    }
+    /// writes the whole buffer as ANSI escape sequences
+    pub fn flush(&self, out: &mut impl Write) -> io::Result<()> {
+        let mut fore = None;
+        let mut back = None;
+        for y in 0..self.height {
+            write!(out, "\x1b[{};1H", y + 1)?;
+            for x in 0..self.width {
+                let cell = self.cells[(x + y * self.width) as usize];
+                if fore != Some(cell.fore) {
+                    self.write_color(out, 38, cell.fore)?;
+                    fore = Some(cell.fore);
+                }
+                if back != Some(cell.back) {
+                    self.write_color(out, 48, cell.back)?;
+                    back = Some(cell.back);
+                }
+                write!(out, "{}", cell.ch)?;
+            }
+        }
+        write!(out, "\x1b[0m")?;
+        out.flush()
+    }
+    fn write_color(&self, out: &mut impl Write, code: u8, col: Color) -> io::Result<()> {
+        match self.color_mode {
+            ColorMode::TrueColor => write!(out, "\x1b[{};2;{};{};{}m", code, col.0, col.1, col.2),
+            ColorMode::Ansi256 => write!(out, "\x1b[{};5;{}m", code, ansi256(col)),
+        }
+    }
+    fn cell(&self, x: Coord, y: Coord) -> Option<&Cell> {
+        if x < 0 || y < 0 || x >= self.width || y >= self.height {
+            return None;
+        }
+        self.cells.get((x + y * self.width) as usize)
+    }
+    fn put(
+        &mut self,
+        x: Coord,
+        y: Coord,
+        ch: Option<char>,
+        fore: Option<Color>,
+        back: Option<Color>,
+    ) {
+        if x < 0 || y < 0 || x >= self.width || y >= self.height {
+            return;
+        }
+        let cell = &mut self.cells[(x + y * self.width) as usize];
+        if let Some(ch) = ch {
+            cell.ch = ch;
+        }
+        if let Some(fore) = fore {
+            cell.fore = fore;
+        }
+        if let Some(back) = back {
+            cell.back = back;
+        }
+    }
+    fn print(&mut self, x: Coord, y: Coord, txt: &str, fore: Option<Color>) {
+        for (i, c) in txt.chars().enumerate() {
+            self.put(x + i as Coord, y, Some(c), fore, None);
+        }
+    }
+}
+
+impl Renderer for TerminalRenderer {
+    fn line(&mut self, p1: Pos, p2: Pos, col: Color) {
+        let glyph = if p2.y - p1.y > p2.x - p1.x {
+            '│'
+        } else {
+            '─'
+        };
+        for x in p1.x..p2.x.max(p1.x + 1) {
+            for y in p1.y..p2.y.max(p1.y + 1) {
+                self.put(x, y, Some(glyph), Some(col), None);
+            }
+        }
+    }
+    fn rectangle(&mut self, rect: &Rect, col: Color) {
+        for x in rect.x..rect.x + rect.w {
+            for y in rect.y..rect.y + rect.h {
+                self.put(x, y, None, None, Some(col));
+            }
+        }
+    }
+    fn text(&mut self, pos: Pos, txt: &str, col: Color) {
+        self.print(pos.x, pos.y, txt, Some(col));
+    }
+    fn frame(&mut self, txt: &str, rect: &Rect, col: Color, coltxt: Color) {
+        let (x1, y1) = (rect.x, rect.y);
+        let (x2, y2) = (rect.x + rect.w - 1, rect.y + rect.h - 1);
+        self.rectangle(rect, col);
+        for x in x1 + 1..x2 {
+            self.put(x, y1, Some('─'), Some(coltxt), None);
+            self.put(x, y2, Some('─'), Some(coltxt), None);
+        }
+        for y in y1 + 1..y2 {
+            self.put(x1, y, Some('│'), Some(coltxt), None);
+            self.put(x2, y, Some('│'), Some(coltxt), None);
+        }
+        self.put(x1, y1, Some('┌'), Some(coltxt), None);
+        self.put(x2, y1, Some('┐'), Some(coltxt), None);
+        self.put(x1, y2, Some('└'), Some(coltxt), None);
+        self.put(x2, y2, Some('┘'), Some(coltxt), None);
+        let len = txt.chars().count() as Coord;
+        self.print(x1 + rect.w / 2 - len / 2, y1, txt, Some(coltxt));
+    }
+    fn checkbox(&mut self, pos: Pos, checked: bool, col: Color) {
+        self.put(
+            pos.x,
+            pos.y,
+            Some(if checked { '▣' } else { '□' }),
+            Some(col),
+            None,
+        );
+    }
+    fn radio(&mut self, pos: Pos, selected: bool, col: Color) {
+        self.put(
+            pos.x,
+            pos.y,
+            Some(if selected { '◉' } else { '○' }),
+            Some(col),
+            None,
+        );
+    }
+    fn dropdown(&mut self, pos: Pos, open: bool, col: Color) {
+        self.put(
+            pos.x,
+            pos.y,
+            Some(if open { '▼' } else { '►' }),
+            Some(col),
+            None,
+        );
+    }
+    fn progress(&mut self, rect: &Rect, val: f32, back: Color, fore: Color) {
+        let wval = (rect.w as f32 * val * 2.0) as Coord;
+        self.rectangle(&Rect::new(rect.x, rect.y, wval / 2, rect.h), fore);
+        self.rectangle(
+            &Rect::new(rect.x + wval / 2, rect.y, rect.w - wval / 2, rect.h),
+            back,
+        );
+        if wval & 1 == 1 {
+            self.put(rect.x + wval / 2, rect.y, Some('▌'), Some(fore), Some(back));
+        }
+    }
+}
+
+/// nearest color in the xterm 256 colors palette
+fn ansi256(col: Color) -> u8 {
+    let (r, g, b) = (col.0 as i32, col.1 as i32, col.2 as i32);
+    let to_cube = |v: i32| {
+        if v < 48 {
+            0
+        } else if v < 115 {
+            1
+        } else {
+            (v - 35) / 40
+        }
+    };
+    let (cr, cg, cb) = (to_cube(r), to_cube(g), to_cube(b));
+    let cube_level = |c: i32| if c == 0 { 0 } else { 55 + c * 40 };
+    let cube_dist =
+        (r - cube_level(cr)).pow(2) + (g - cube_level(cg)).pow(2) + (b - cube_level(cb)).pow(2);
+    let gray = ((r + g + b) / 3).clamp(8, 238);
+    let gray_index = (gray - 8) / 10;
+    let gray_level = 8 + gray_index * 10;
+    let gray_dist = (r - gray_level).pow(2) + (g - gray_level).pow(2) + (b - gray_level).pow(2);
+    if gray_dist < cube_dist {
+        (232 + gray_index) as u8
+    } else {
+        (16 + 36 * cr + 6 * cg + cb) as u8
+    }
+}
+
+/// forwards the terminal events received since the last frame to the context
+pub fn update_terminal_input_data(events: &[Event], ctx: &mut Context) {
+    let mut text = String::new();
+    let mut modifiers = 0;
+    for event in events.iter() {
+        match event {
+            Event::Key(key) => {
+                modifiers = key_modifiers(key.modifiers);
+                if let KeyCode::Char(c) = key.code {
+                    if c.is_ascii_alphanumeric() {
+                        ctx.input_char_key(c);
+                    }
+                }
+                match key.code {
+                    KeyCode::Char(c) if modifiers & (KEY_MOD_CTRL | KEY_MOD_ALT) == 0 => {
+                        text.push(c);
+                        if c == ' ' {
+                            ctx.input_special_key(SpecialKey::Space);
+                        }
+                    }
+                    KeyCode::Char(c) if modifiers & KEY_MOD_CTRL != 0 => match c {
+                        'a' => ctx.input_special_key(SpecialKey::SelectAll),
+                        'c' => ctx.input_special_key(SpecialKey::Copy),
+                        'x' => ctx.input_special_key(SpecialKey::Cut),
+                        'v' => ctx.input_special_key(SpecialKey::Paste),
+                        'z' => ctx.input_special_key(SpecialKey::Undo),
+                        'y' => ctx.input_special_key(SpecialKey::Redo),
+                        _ => (),
+                    },
+                    KeyCode::Backspace => ctx.input_special_key(SpecialKey::Backspace),
+                    KeyCode::Delete => ctx.input_special_key(SpecialKey::Delete),
+                    KeyCode::Left => ctx.input_special_key(SpecialKey::Left),
+                    KeyCode::Right => ctx.input_special_key(SpecialKey::Right),
+                    KeyCode::Up => ctx.input_special_key(SpecialKey::Up),
+                    KeyCode::Down => ctx.input_special_key(SpecialKey::Down),
+                    KeyCode::Home => ctx.input_special_key(SpecialKey::Home),
+                    KeyCode::End => ctx.input_special_key(SpecialKey::End),
+                    KeyCode::PageUp => ctx.input_special_key(SpecialKey::PageUp),
+                    KeyCode::PageDown => ctx.input_special_key(SpecialKey::PageDown),
+                    KeyCode::Tab => ctx.input_special_key(SpecialKey::Tab),
+                    KeyCode::BackTab => {
+                        modifiers |= KEY_MOD_SHIFT;
+                        ctx.input_special_key(SpecialKey::Tab);
+                    }
+                    KeyCode::Enter => ctx.input_special_key(SpecialKey::Enter),
+                    KeyCode::Esc => ctx.input_special_key(SpecialKey::Escape),
+                    _ => (),
+                }
+            }
+            Event::Mouse(mouse) => match *mouse {
+                MouseEvent::Down(button, x, y, _) => {
+                    ctx.input_mouse_pos(x as f32, y as f32);
+                    ctx.input_mouse_down(mouse_button(button));
+                }
+                MouseEvent::Up(button, x, y, _) => {
+                    ctx.input_mouse_pos(x as f32, y as f32);
+                    ctx.input_mouse_up(mouse_button(button));
+                }
+                MouseEvent::Drag(_, x, y, _) => ctx.input_mouse_pos(x as f32, y as f32),
+                MouseEvent::ScrollDown(x, y, _) => {
+                    ctx.input_mouse_pos(x as f32, y as f32);
+                    ctx.input_mouse_wheel(-1);
+                }
+                MouseEvent::ScrollUp(x, y, _) => {
+                    ctx.input_mouse_pos(x as f32, y as f32);
+                    ctx.input_mouse_wheel(1);
+                }
+            },
+            Event::Resize(w, h) => ctx.input_screen_size(*w as Coord, *h as Coord),
+        }
+    }
+    ctx.input_key_modifiers(modifiers);
+    ctx.input_text(text);
+}
+
+fn key_modifiers(modifiers: KeyModifiers) -> usize {
+    let mut ret = 0;
+    if modifiers.contains(KeyModifiers::SHIFT) {
+        ret |= KEY_MOD_SHIFT;
+    }
+    if modifiers.contains(KeyModifiers::CONTROL) {
+        ret |= KEY_MOD_CTRL;
+    }
+    if modifiers.contains(KeyModifiers::ALT) {
+        ret |= KEY_MOD_ALT;
+    }
+    ret
+}
+
+fn mouse_button(button: MouseButton) -> usize {
+    match button {
+        MouseButton::Left => MOUSE_BUTTON_LEFT,
+        MouseButton::Right => MOUSE_BUTTON_RIGHT,
+        MouseButton::Middle => MOUSE_BUTTON_MIDDLE,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_ansi256() {
+        assert_eq!(ansi256((0, 0, 0, 255)), 16);
+        assert_eq!(ansi256((255, 0, 0, 255)), 196);
+        assert_eq!(ansi256((128, 128, 128, 255)), 244);
+    }
+    #[test]
+    fn test_render() {
+        let mut rend = TerminalRenderer::new(10, 2);
+        rend.color_mode(ColorMode::Ansi256);
+        let mut ctx = Context::new();
+        ctx.begin();
+        ctx.label("hello");
+        ctx.end();
+        ctx.render(&mut rend);
+        assert_eq!(rend.char_at(0, 0), Some('h'));
+        assert_eq!(rend.char_at(4, 0), Some('o'));
+        let mut out = Vec::new();
+        rend.flush(&mut out).unwrap();
+        let out = String::from_utf8(out).unwrap();
+        assert!(out.starts_with("\x1b[1;1H\x1b[38;5;"));
+        assert!(out.contains("hello"));
+    }
+}
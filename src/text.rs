@@ -1,10 +1,13 @@
 use unicode_segmentation::UnicodeSegmentation;
 
 use crate::{
-    ColorCode, Context, Coord, DeferedCommand, Id, SpecialKey, TextBoxState, MOUSE_BUTTON_LEFT,
+    Clipboard, ColorCode, Context, Coord, DeferedCommand, Id, Pos, Rect, SpecialKey, TextAlign,
+    TextBoxState, KEY_MOD_CTRL, KEY_MOD_SHIFT, MOUSE_BUTTON_LEFT,
 };
 
-const CURSOR_DELAY: usize = 10;
+/// blinking period of the cursor in seconds
+const CURSOR_BLINK_TIME: f64 = 0.5;
+const UNDO_LIMIT: usize = 100;
 
 impl Context {
     // =======================================================
@@ -25,28 +28,57 @@ impl Context {
         self.update_control(id, &r, false);
         let focus = self.focus == id;
         let hover = self.hover == id;
-        let (current_value, bkgnd_text, cursor, offset) =
-            self.update_text_state(id, bkgnd_text, default_value, focus, r.w as usize);
-        self.pressed = hover && self.mouse_pressed == MOUSE_BUTTON_LEFT;
+        self.textbox_field(id, r, focus, hover, default_value, bkgnd_text);
+        self.pressed = hover && self.mouse_pressed & MOUSE_BUTTON_LEFT != 0;
+        self
+    }
+    /// edits and draws the content of a single line text field.
+    /// Used by the widgets embedding a textbox
+    pub(crate) fn textbox_field(
+        &mut self,
+        id: Id,
+        r: Rect,
+        focus: bool,
+        hover: bool,
+        default_value: Option<&str>,
+        bkgnd_text: Option<&str>,
+    ) {
+        self.update_text_state(id, bkgnd_text, default_value, focus, hover, r);
+        let state = &self.textbox_state[&id];
         let background_code = if hover || focus {
             ColorCode::ButtonBackgroundFocus
         } else {
             ColorCode::ButtonBackgroundHover
         };
-        let foreground_code = if current_value.is_empty() {
+        let foreground_code = if state.value.is_empty() {
             ColorCode::ButtonTextDisabled
         } else {
             ColorCode::ButtonText
         };
-        let back = self.get_color(background_code);
-        let fore = self.get_color(foreground_code);
-        let mut value = if current_value.is_empty() && !focus {
-            bkgnd_text
+        let mut value = if state.value.is_empty() && !focus {
+            state.bkgnd_text.to_owned()
         } else {
-            current_value
+            state.value.to_owned()
         };
-        if focus && self.timer % CURSOR_DELAY < CURSOR_DELAY / 2 {
-            value = add_cursor(&value, cursor);
+        let offset = state.offset;
+        let selection = if focus { state.selection() } else { None };
+        // visible part of the selection
+        let selected = selection.and_then(|(start, end)| {
+            let start = start.max(offset);
+            let end = end.min(offset + r.w as usize);
+            if start < end {
+                let text: String = value
+                    .graphemes(true)
+                    .skip(start)
+                    .take(end - start)
+                    .collect();
+                Some((start - offset, end - start, text))
+            } else {
+                None
+            }
+        });
+        if selection.is_none() && focus && self.time % CURSOR_BLINK_TIME < CURSOR_BLINK_TIME / 2.0 {
+            value = add_cursor(&value, state.cursor_pos);
         }
         if offset > 0 {
             value = value
@@ -55,83 +87,507 @@ impl Context {
                 .collect::<Vec<&str>>()
                 .join("");
         }
+        let back = self.get_color(background_code);
+        let fore = self.get_color(foreground_code);
         self.defered(DeferedCommand::Label(r, value, back, fore));
-        self
+        if let Some((x, len, text)) = selected {
+            let sel_rect = Rect::new(r.x + x as Coord, r.y, len as Coord, 1);
+            let back = self.get_color(ColorCode::TextSelection);
+            let fore = self.get_color(ColorCode::ButtonTextFocus);
+            self.defered(DeferedCommand::Label(sel_rect, text, back, fore));
+        }
     }
-    /// change the value of a textbox. Resets cursor position to 0.
+    /// change the value of a textbox or textarea. Resets cursor position to 0.
     pub fn set_textbox_value(&mut self, textbox_id: Id, value: &str) {
         if let Some(ref mut state) = self.textbox_state.get_mut(&textbox_id) {
             state.value = value.to_owned();
             state.cursor_pos = 0;
             state.offset = 0;
+            state.line_offset = 0;
+            state.selection_anchor = None;
         }
     }
-    /// returns (value, bkgnd_text, cursor_pos, offset)
     fn update_text_state(
         &mut self,
         id: Id,
         bkgnd_text: Option<&str>,
         default_value: Option<&str>,
         focus: bool,
-        width: usize,
-    ) -> (String, String, usize, usize) {
-        {
-            let state = self.textbox_state.entry(id).or_insert(TextBoxState {
-                bkgnd_text: bkgnd_text.map_or(String::new(), |t| t.to_owned()),
-                value: default_value.map_or(String::new(), |t| t.to_owned()),
-                offset: 0,
-                cursor_pos: 0,
+        hover: bool,
+        r: Rect,
+    ) {
+        let width = r.w as usize;
+        let state = self.textbox_state.entry(id).or_insert(TextBoxState {
+            bkgnd_text: bkgnd_text.map_or(String::new(), |t| t.to_owned()),
+            value: default_value.map_or(String::new(), |t| t.to_owned()),
+            ..Default::default()
+        });
+        if !focus {
+            state.selection_anchor = None;
+            return;
+        }
+        let select = self.key_modifiers & KEY_MOD_SHIFT != 0;
+        if hover && self.mouse_pressed & MOUSE_BUTTON_LEFT != 0 {
+            // move the cursor under the mouse. shift+click extends the selection
+            let slen = state.value.graphemes(true).count();
+            let pos = state.offset + (self.mouse_pos.0 as Coord - r.x) as usize;
+            state.move_cursor(pos.min(slen), select);
+        }
+        let keys: Vec<SpecialKey> = self.special_keys.drain(0..).collect();
+        for k in keys {
+            if edit_text(
+                state,
+                k,
+                self.key_modifiers,
+                self.clipboard.0.as_mut(),
+                false,
+            ) {
+                continue;
+            }
+            let slen = state.value.graphemes(true).count();
+            match k {
+                SpecialKey::End => state.move_cursor(slen, select),
+                SpecialKey::Home => state.move_cursor(0, select),
+                // navigation keys are left to the focus chain
+                _ => self.special_keys.push(k),
+            }
+        }
+        let typed: String = self
+            .text_input
+            .chars()
+            .filter(|c| !c.is_control())
+            .collect();
+        if !typed.is_empty() {
+            state.insert(&typed, true);
+        }
+        state.offset = state.offset.min(state.cursor_pos);
+        if state.cursor_pos >= width {
+            state.offset = state.offset.max(state.cursor_pos + 1 - width);
+        }
+    }
+    // =======================================================
+    //
+    // Multi-line text area
+    //
+    // =======================================================
+    /// a multi-line text editor. Enter inserts a new line.
+    /// Lines longer than the width are scrolled horizontally.
+    pub fn textarea(
+        &mut self,
+        id: &str,
+        width: Coord,
+        height: Coord,
+        default_value: Option<&str>,
+        bkgnd_text: Option<&str>,
+    ) -> &mut Self {
+        self.try_commit();
+        let id = self.generate_id(id);
+        let r = self.next_rectangle(width, height);
+        self.update_control(id, &r, false);
+        let focus = self.focus == id;
+        let hover = self.hover == id;
+        self.update_textarea_state(id, bkgnd_text, default_value, focus, hover, r);
+        self.pressed = hover && self.mouse_pressed & MOUSE_BUTTON_LEFT != 0;
+        let state = &self.textbox_state[&id];
+        let background_code = if hover || focus {
+            ColorCode::ButtonBackgroundFocus
+        } else {
+            ColorCode::ButtonBackgroundHover
+        };
+        let foreground_code = if state.value.is_empty() {
+            ColorCode::ButtonTextDisabled
+        } else {
+            ColorCode::ButtonText
+        };
+        let (cursor_line, cursor_col) = cursor_line_col(&state.value, state.cursor_pos);
+        let value = if state.value.is_empty() && !focus {
+            &state.bkgnd_text
+        } else {
+            &state.value
+        };
+        let (line_offset, offset) = (state.line_offset, state.offset);
+        let lines: Vec<String> = value
+            .split('\n')
+            .skip(line_offset)
+            .take(r.h as usize)
+            .map(|l| l.to_owned())
+            .collect();
+        let selection = if focus { state.selection() } else { None };
+        let line_start = line_col_to_cursor(value, line_offset, 0);
+        let show_cursor =
+            selection.is_none() && focus && self.time % CURSOR_BLINK_TIME < CURSOR_BLINK_TIME / 2.0;
+        let back = self.get_color(background_code);
+        let fore = self.get_color(foreground_code);
+        let sel_back = self.get_color(ColorCode::TextSelection);
+        let sel_fore = self.get_color(ColorCode::ButtonTextFocus);
+        self.draw_rect(r, back);
+        let mut pos = line_start;
+        for (i, line) in lines.iter().enumerate() {
+            let len = line.graphemes(true).count();
+            // selected columns on this line
+            let selected = selection.and_then(|(start, end)| {
+                let start = start.max(pos) - pos;
+                let end = end.min(pos + len).saturating_sub(pos);
+                if start < end {
+                    Some((start, end))
+                } else {
+                    None
+                }
             });
-            if focus {
-                for k in self.special_keys.drain(0..) {
-                    let slen = state.value.graphemes(true).count();
-                    match k {
-                        SpecialKey::Backspace => {
-                            if state.cursor_pos > 0 {
-                                state.value = remove_grapheme(&state.value, state.cursor_pos - 1);
-                                state.cursor_pos -= 1;
-                            }
-                        }
-                        SpecialKey::Delete => {
-                            if state.cursor_pos < slen {
-                                state.value = remove_grapheme(&state.value, state.cursor_pos);
-                            }
-                        }
-                        SpecialKey::Left => {
-                            if state.cursor_pos > 0 {
-                                state.cursor_pos -= 1;
-                            }
-                        }
-                        SpecialKey::Right => {
-                            if state.cursor_pos < slen {
-                                state.cursor_pos += 1;
-                            }
-                        }
-                        SpecialKey::End => {
-                            state.cursor_pos = slen;
-                        }
-                        SpecialKey::Home => {
-                            state.cursor_pos = 0;
-                        }
-                    }
+            pos += len + 1;
+            let line = if show_cursor && line_offset + i == cursor_line {
+                add_cursor(line, cursor_col)
+            } else {
+                line.to_owned()
+            };
+            let visible: String = line
+                .graphemes(true)
+                .skip(offset)
+                .take(r.w as usize)
+                .collect();
+            let line_rect = Rect::new(r.x, r.y + i as Coord, r.w, 1);
+            self.draw_text(line_rect, &visible, TextAlign::Left, fore);
+            if let Some((start, end)) = selected {
+                let start = start.max(offset);
+                let end = end.min(offset + r.w as usize);
+                if start < end {
+                    let text: String = line.graphemes(true).skip(start).take(end - start).collect();
+                    let sel_rect = Rect::new(
+                        r.x + (start - offset) as Coord,
+                        line_rect.y,
+                        (end - start) as Coord,
+                        1,
+                    );
+                    self.draw_rect(sel_rect, sel_back);
+                    self.draw_text(sel_rect, &text, TextAlign::Left, sel_fore);
+                }
+            }
+        }
+        self
+    }
+    fn update_textarea_state(
+        &mut self,
+        id: Id,
+        bkgnd_text: Option<&str>,
+        default_value: Option<&str>,
+        focus: bool,
+        hover: bool,
+        r: Rect,
+    ) {
+        let (width, height) = (r.w as usize, r.h as usize);
+        let state = self.textbox_state.entry(id).or_insert(TextBoxState {
+            bkgnd_text: bkgnd_text.map_or(String::new(), |t| t.to_owned()),
+            value: default_value.map_or(String::new(), |t| t.to_owned()),
+            ..Default::default()
+        });
+        let mut moved = false;
+        if !focus {
+            state.selection_anchor = None;
+        } else {
+            let select = self.key_modifiers & KEY_MOD_SHIFT != 0;
+            if hover && self.mouse_pressed & MOUSE_BUTTON_LEFT != 0 {
+                // move the cursor under the mouse. shift+click extends the selection
+                let mouse_pos: Pos = self.mouse_pos.into();
+                let line = state.line_offset + (mouse_pos.y - r.y) as usize;
+                let col = state.offset + (mouse_pos.x - r.x) as usize;
+                state.move_cursor(line_col_to_cursor(&state.value, line, col), select);
+                moved = true;
+            }
+            let keys: Vec<SpecialKey> = self.special_keys.drain(0..).collect();
+            for k in keys {
+                moved = true;
+                if edit_text(
+                    state,
+                    k,
+                    self.key_modifiers,
+                    self.clipboard.0.as_mut(),
+                    true,
+                ) {
+                    continue;
                 }
-                if !self.text_input.is_empty() {
-                    state.value = insert_text(&state.value, state.cursor_pos, &self.text_input);
-                    state.cursor_pos += self.text_input.graphemes(true).count();
+                let (line, col) = cursor_line_col(&state.value, state.cursor_pos);
+                let new_line = match k {
+                    SpecialKey::Up => line.saturating_sub(1),
+                    SpecialKey::Down => line + 1,
+                    SpecialKey::PageUp => line.saturating_sub(height),
+                    SpecialKey::PageDown => line + height,
+                    _ => line,
+                };
+                match k {
+                    SpecialKey::Up
+                    | SpecialKey::Down
+                    | SpecialKey::PageUp
+                    | SpecialKey::PageDown => {
+                        let pos = if new_line != line {
+                            line_col_to_cursor(&state.value, new_line, col)
+                        } else {
+                            state.cursor_pos
+                        };
+                        state.move_cursor(pos, select);
+                    }
+                    SpecialKey::Home => {
+                        state.move_cursor(line_col_to_cursor(&state.value, line, 0), select);
+                    }
+                    SpecialKey::End => {
+                        let pos = line_col_to_cursor(&state.value, line, usize::MAX);
+                        state.move_cursor(pos, select);
+                    }
+                    SpecialKey::Enter => state.insert("\n", false),
+                    // navigation keys are left to the focus chain
+                    _ => {
+                        moved = false;
+                        self.special_keys.push(k);
+                    }
                 }
-                state.offset = state.offset.min(state.cursor_pos);
-                if state.cursor_pos >= width {
-                    state.offset = state.offset.max(state.cursor_pos + 1 - width);
+            }
+            let typed: String = self
+                .text_input
+                .chars()
+                .filter(|c| !c.is_control())
+                .collect();
+            if !typed.is_empty() {
+                state.insert(&typed, true);
+                moved = true;
+            }
+        }
+        if moved {
+            // keep the cursor visible
+            let (line, col) = cursor_line_col(&state.value, state.cursor_pos);
+            state.line_offset = state.line_offset.min(line);
+            if line >= height {
+                state.line_offset = state.line_offset.max(line + 1 - height);
+            }
+            state.offset = state.offset.min(col);
+            if col >= width {
+                state.offset = state.offset.max(col + 1 - width);
+            }
+        } else if hover && self.mouse_wheel != 0 {
+            let line_count = state.value.split('\n').count();
+            let max_offset = line_count.saturating_sub(height) as Coord;
+            state.line_offset =
+                (state.line_offset as Coord - self.mouse_wheel).clamp(0, max_offset) as usize;
+            self.mouse_wheel = 0;
+        }
+    }
+}
+
+impl TextBoxState {
+    /// start and end of the selected graphemes, if any
+    fn selection(&self) -> Option<(usize, usize)> {
+        match self.selection_anchor {
+            Some(anchor) if anchor != self.cursor_pos => {
+                Some((anchor.min(self.cursor_pos), anchor.max(self.cursor_pos)))
+            }
+            _ => None,
+        }
+    }
+    fn selected_text(&self) -> Option<String> {
+        self.selection().map(|(start, end)| {
+            self.value
+                .graphemes(true)
+                .skip(start)
+                .take(end - start)
+                .collect()
+        })
+    }
+    /// move the cursor, extending the selection if select is true
+    fn move_cursor(&mut self, pos: usize, select: bool) {
+        if !select {
+            self.selection_anchor = None;
+        } else if self.selection_anchor.is_none() {
+            self.selection_anchor = Some(self.cursor_pos);
+        }
+        self.cursor_pos = pos;
+        self.typing = false;
+    }
+    fn save_undo(&mut self) {
+        self.undo_stack
+            .push((self.value.to_owned(), self.cursor_pos));
+        if self.undo_stack.len() > UNDO_LIMIT {
+            self.undo_stack.remove(0);
+        }
+        self.redo_stack.clear();
+    }
+    fn delete_range(&mut self, start: usize, end: usize) {
+        self.save_undo();
+        self.value = self
+            .value
+            .graphemes(true)
+            .enumerate()
+            .filter(|(i, _)| *i < start || *i >= end)
+            .map(|(_, g)| g)
+            .collect();
+        self.cursor_pos = start;
+        self.selection_anchor = None;
+        self.typing = false;
+    }
+    /// replace the selection with txt
+    fn insert(&mut self, txt: &str, typing: bool) {
+        if let Some((start, end)) = self.selection() {
+            self.delete_range(start, end);
+        } else if !(typing && self.typing) {
+            self.save_undo();
+        }
+        self.selection_anchor = None;
+        self.value = insert_text(&self.value, self.cursor_pos, txt);
+        self.cursor_pos += txt.graphemes(true).count();
+        self.typing = typing;
+    }
+    fn undo(&mut self) {
+        if let Some((value, cursor_pos)) = self.undo_stack.pop() {
+            let current = std::mem::replace(&mut self.value, value);
+            self.redo_stack.push((current, self.cursor_pos));
+            self.cursor_pos = cursor_pos;
+            self.selection_anchor = None;
+            self.typing = false;
+        }
+    }
+    fn redo(&mut self) {
+        if let Some((value, cursor_pos)) = self.redo_stack.pop() {
+            let current = std::mem::replace(&mut self.value, value);
+            self.undo_stack.push((current, self.cursor_pos));
+            self.cursor_pos = cursor_pos;
+            self.selection_anchor = None;
+            self.typing = false;
+        }
+    }
+}
+
+/// applies the editing keys shared by textbox and textarea.
+/// returns false if the key was not handled
+fn edit_text(
+    state: &mut TextBoxState,
+    key: SpecialKey,
+    modifiers: usize,
+    clipboard: &mut dyn Clipboard,
+    multiline: bool,
+) -> bool {
+    let slen = state.value.graphemes(true).count();
+    let select = modifiers & KEY_MOD_SHIFT != 0;
+    let word = modifiers & KEY_MOD_CTRL != 0;
+    match key {
+        SpecialKey::Backspace => {
+            if let Some((start, end)) = state.selection() {
+                state.delete_range(start, end);
+            } else if state.cursor_pos > 0 {
+                state.delete_range(state.cursor_pos - 1, state.cursor_pos);
+            }
+        }
+        SpecialKey::Delete => {
+            if let Some((start, end)) = state.selection() {
+                state.delete_range(start, end);
+            } else if state.cursor_pos < slen {
+                state.delete_range(state.cursor_pos, state.cursor_pos + 1);
+            }
+        }
+        SpecialKey::Left => {
+            let pos = match state.selection() {
+                _ if word => prev_word(&state.value, state.cursor_pos),
+                // collapse the selection
+                Some((start, _)) if !select => start,
+                _ => state.cursor_pos.saturating_sub(1),
+            };
+            state.move_cursor(pos, select);
+        }
+        SpecialKey::Right => {
+            let pos = match state.selection() {
+                _ if word => next_word(&state.value, state.cursor_pos),
+                Some((_, end)) if !select => end,
+                _ => (state.cursor_pos + 1).min(slen),
+            };
+            state.move_cursor(pos, select);
+        }
+        SpecialKey::SelectAll => {
+            state.selection_anchor = Some(0);
+            state.cursor_pos = slen;
+        }
+        SpecialKey::Copy => {
+            if let Some(text) = state.selected_text() {
+                clipboard.set_text(&text);
+            }
+        }
+        SpecialKey::Cut => {
+            if let Some(text) = state.selected_text() {
+                clipboard.set_text(&text);
+                if let Some((start, end)) = state.selection() {
+                    state.delete_range(start, end);
                 }
             }
-            (
-                state.value.to_owned(),
-                state.bkgnd_text.to_owned(),
-                state.cursor_pos,
-                state.offset,
-            )
         }
+        SpecialKey::Paste => {
+            if let Some(text) = clipboard.get_text() {
+                let text: String = text
+                    .chars()
+                    .map(|c| if c == '\n' && !multiline { ' ' } else { c })
+                    .filter(|c| *c == '\n' || !c.is_control())
+                    .collect();
+                state.insert(&text, false);
+            }
+        }
+        SpecialKey::Undo => state.undo(),
+        SpecialKey::Redo => state.redo(),
+        _ => return false,
+    }
+    true
+}
+
+fn is_word_grapheme(g: &str) -> bool {
+    g.chars().any(|c| c.is_alphanumeric() || c == '_')
+}
+
+/// start of the word before the cursor
+fn prev_word(value: &str, cursor: usize) -> usize {
+    let graphemes: Vec<&str> = value.graphemes(true).take(cursor).collect();
+    let mut pos = graphemes.len();
+    while pos > 0 && !is_word_grapheme(graphemes[pos - 1]) {
+        pos -= 1;
+    }
+    while pos > 0 && is_word_grapheme(graphemes[pos - 1]) {
+        pos -= 1;
+    }
+    pos
+}
+
+/// start of the word after the cursor
+fn next_word(value: &str, cursor: usize) -> usize {
+    let graphemes: Vec<&str> = value.graphemes(true).collect();
+    let mut pos = cursor.min(graphemes.len());
+    while pos < graphemes.len() && is_word_grapheme(graphemes[pos]) {
+        pos += 1;
+    }
+    while pos < graphemes.len() && !is_word_grapheme(graphemes[pos]) {
+        pos += 1;
+    }
+    pos
+}
+
+/// line and column of a cursor position in a multi-line text
+fn cursor_line_col(value: &str, cursor: usize) -> (usize, usize) {
+    let mut line = 0;
+    let mut col = 0;
+    for g in value.graphemes(true).take(cursor) {
+        if g == "\n" {
+            line += 1;
+            col = 0;
+        } else {
+            col += 1;
+        }
+    }
+    (line, col)
+}
+
+/// cursor position of a line and column in a multi-line text.
+/// The column is clamped to the line length, lines past the end go to the end of the text
+fn line_col_to_cursor(value: &str, line: usize, col: usize) -> usize {
+    let mut pos = 0;
+    for (i, l) in value.split('\n').enumerate() {
+        let len = l.graphemes(true).count();
+        if i == line {
+            return pos + col.min(len);
+        }
+        pos += len + 1;
     }
+    value.graphemes(true).count()
 }
 
 fn add_cursor(value: &str, cursor: usize) -> String {
@@ -177,13 +633,3 @@ fn insert_text(value: &str, cursor: usize, txt: &str) -> String {
     };
     head.to_owned() + txt + &tail
 }
-
-fn remove_grapheme(value: &str, pos: usize) -> String {
-    let ret: String = value
-        .graphemes(true)
-        .enumerate()
-        .filter(|(i, _)| *i != pos)
-        .map(|(_, v)| v)
-        .collect();
-    ret
-}
@@ -0,0 +1,156 @@
+use crate::{
+    ColorCode, Command, Context, Coord, Id, Pos, Rect, SpecialKey, TextAlign, TreeLevel,
+    MOUSE_BUTTON_LEFT, NULL_ID,
+};
+
+impl Context {
+    // =======================================================
+    //
+    // Tree view
+    //
+    // =======================================================
+    /// starts a hierarchical outline. It can have several top level nodes
+    /// and has a single selected node.
+    /// ctx.last_id() returns the tree id right after this call
+    pub fn tree_begin(&mut self, id: &str) {
+        self.tree_id = self.generate_id(id);
+        self.prefix_id(id);
+    }
+    pub fn tree_end(&mut self) {
+        assert!(
+            self.tree_stack.is_empty(),
+            "unmatched tree_node_begin/tree_node_end calls"
+        );
+        self.tree_id = NULL_ID;
+        self.id_prefix.pop();
+    }
+    /// a node of the outline. Returns true if the node is expanded :
+    /// its children should then be built before calling tree_node_end.
+    /// tree_node_end must be called whether the node is expanded or not.
+    /// Clicking the arrow or pressing Enter/Space expands or collapses the node.
+    /// The focused node is the selected one. Up/Down move the selection,
+    /// Left collapses the node or selects its parent, Right expands the node.
+    /// ctx.last_id() returns the node id right after this call.
+    pub fn tree_node_begin(&mut self, id: &str, label: &str, default_open: bool) -> bool {
+        let node_id = self.tree_node(id, label, Some(default_open));
+        let open = self.button_state.get(&node_id) == Some(&1);
+        self.prefix_id(id);
+        self.tree_stack.push(TreeLevel {
+            id: node_id,
+            last_child: NULL_ID,
+        });
+        open
+    }
+    pub fn tree_node_end(&mut self) {
+        let level = self
+            .tree_stack
+            .pop()
+            .expect("unmatched tree_node_begin/tree_node_end calls");
+        self.tree_last_child.insert(level.id, level.last_child);
+        self.id_prefix.pop();
+    }
+    /// a node without children. Returns true if it's selected
+    pub fn tree_leaf(&mut self, id: &str, label: &str) -> bool {
+        let node_id = self.tree_node(id, label, None);
+        self.tree_selection.get(&self.tree_id) == Some(&node_id)
+    }
+    /// the id of the selected node of a tree
+    pub fn tree_selection(&self, tree_id: Id) -> Option<Id> {
+        self.tree_selection.get(&tree_id).copied()
+    }
+    pub fn set_tree_selection(&mut self, tree_id: Id, node_id: Id) {
+        self.tree_selection.insert(tree_id, node_id);
+        self.set_focus(node_id);
+    }
+    /// draws a tree node row. open is None for the leaves
+    fn tree_node(&mut self, id: &str, label: &str, open: Option<bool>) -> Id {
+        assert!(
+            self.tree_id != NULL_ID,
+            "tree node built outside tree_begin/tree_end"
+        );
+        self.try_commit();
+        let node_id = self.generate_id(id);
+        let depth = self.tree_stack.len() as Coord;
+        let parent = self.tree_stack.last().map_or(NULL_ID, |level| level.id);
+        let r = self.next_rectangle(depth * 2 + 2 + label.chars().count() as Coord, 1);
+        self.update_control(node_id, &r, false);
+        let focus = self.focus == node_id;
+        let hover = self.hover == node_id;
+        let arrow = Pos {
+            x: r.x + depth * 2,
+            y: r.y,
+        };
+        let mut on = open.map(|default| {
+            *self
+                .button_state
+                .entry(node_id)
+                .or_insert(if default { 1 } else { 0 })
+                == 1
+        });
+        let tree_id = self.tree_id;
+        if focus {
+            self.tree_selection.insert(tree_id, node_id);
+        }
+        if let Some(on) = on.as_mut() {
+            let arrow_clicked = hover
+                && self.mouse_pressed & MOUSE_BUTTON_LEFT != 0
+                && self.mouse_pos.0 as Coord == arrow.x;
+            if arrow_clicked || self.key_activated(node_id) {
+                *on = !*on;
+            } else if focus && *on && self.take_special_key(SpecialKey::Left) {
+                *on = false;
+            } else if focus && !*on && self.take_special_key(SpecialKey::Right) {
+                *on = true;
+            }
+            self.button_state.insert(node_id, if *on { 1 } else { 0 });
+        }
+        if focus && parent != NULL_ID && self.take_special_key(SpecialKey::Left) {
+            self.set_focus(parent);
+        }
+        let back = self.get_color(if self.tree_selection.get(&tree_id) == Some(&node_id) {
+            ColorCode::TextSelection
+        } else if focus || hover {
+            ColorCode::ButtonBackgroundHover
+        } else {
+            ColorCode::Background
+        });
+        let fore = self.get_color(ColorCode::Text);
+        self.draw_rect(r, back);
+        let guides = self.tree_guides(node_id, open.is_none());
+        self.draw_text(r, &guides, TextAlign::Left, fore);
+        if let Some(on) = on {
+            self.push_command(Command::DropDown(arrow, on, fore));
+        }
+        let label_area = Rect::new(arrow.x + 2, r.y, r.w - depth * 2 - 2, 1);
+        self.draw_text(label_area, label, TextAlign::Left, fore);
+        if let Some(level) = self.tree_stack.last_mut() {
+            level.last_child = node_id;
+        }
+        node_id
+    }
+    /// the indentation guides of a node. The last children of their parent
+    /// (according to the previous frame) are connected with a corner
+    fn tree_guides(&self, node_id: Id, leaf: bool) -> String {
+        let mut guides = String::new();
+        let is_last = |parent: Id, child: Id| self.tree_last_child.get(&parent) == Some(&child);
+        for pair in self.tree_stack.windows(2) {
+            // the ancestors guides
+            guides += if is_last(pair[0].id, pair[1].id) {
+                "  "
+            } else {
+                "│ "
+            };
+        }
+        if let Some(parent) = self.tree_stack.last() {
+            guides += if is_last(parent.id, node_id) {
+                "└─"
+            } else {
+                "├─"
+            };
+            if leaf {
+                guides.push('─');
+            }
+        }
+        guides
+    }
+}